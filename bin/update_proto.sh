@@ -2,7 +2,9 @@
 # take from https://github.com/mindriot101/rust-etcd3
 
 protodir=proto
-sources=(api/etcdserverpb/rpc.proto api/mvccpb/kv.proto api/authpb/auth.proto)
+sources=(api/etcdserverpb/rpc.proto api/mvccpb/kv.proto api/authpb/auth.proto
+  server/etcdserver/api/v3lock/v3lockpb/v3lock.proto
+  server/etcdserver/api/v3election/v3electionpb/v3election.proto)
 branch="${1:-v3.5.20}"
 
 mkdir -p $protodir
@@ -30,4 +30,20 @@ fn gen_pb_code() {
         .out_dir("src/pb/")
         .compile_protos(&["proto/rpc.proto"], &["proto/"])
         .unwrap_or_else(|e| panic!("protobuf compilation failed: {}", e));
+
+    // Build v3lock.proto
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(false)
+        .out_dir("src/pb/")
+        .compile_protos(&["proto/v3lock.proto"], &["proto/"])
+        .unwrap_or_else(|e| panic!("protobuf compilation failed: {}", e));
+
+    // Build v3election.proto
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(false)
+        .out_dir("src/pb/")
+        .compile_protos(&["proto/v3election.proto"], &["proto/"])
+        .unwrap_or_else(|e| panic!("protobuf compilation failed: {}", e));
 }
@@ -0,0 +1,29 @@
+use etcdv3client::{Error, EtcdClient};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let endpoint = "http://localhost:2379";
+    let cred = None;
+    let mut client = EtcdClient::new(vec![endpoint], cred).await?;
+
+    let status = client.maintenance.get_status().await?;
+    println!(
+        "etcd {} db size {} bytes, leader {:x}",
+        status.version, status.db_size, status.leader
+    );
+
+    let mut file = tokio::fs::File::create("snapshot.db")
+        .await
+        .expect("create snapshot file failed");
+
+    let written = client
+        .maintenance
+        .save_snapshot(&mut file, |received, remaining| {
+            println!("received {} bytes, {} bytes remaining", received, remaining);
+        })
+        .await?;
+
+    println!("snapshot saved, {} bytes written", written);
+
+    Ok(())
+}
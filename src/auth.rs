@@ -1,7 +1,9 @@
 use crate::error::Result;
 use crate::grpc::GrpcService;
 use crate::pb;
+use crate::utils::{new_request, with_deadline};
 
+use std::time::Duration;
 use tonic::IntoRequest;
 
 #[derive(Debug, Clone)]
@@ -321,7 +323,7 @@ where
         }
     }
 
-    pub fn do_auth_enable(&mut self) -> DoAuthEnableRequest<S> {
+    pub fn do_auth_enable(&mut self) -> DoAuthEnableRequest<'_, S> {
         pb::AuthEnableRequest::default().build(self)
     }
 
@@ -329,7 +331,7 @@ where
         self.do_auth_enable().await.map(|_| ())
     }
 
-    pub fn do_auth_disable(&mut self) -> DoAuthDisableRequest<S> {
+    pub fn do_auth_disable(&mut self) -> DoAuthDisableRequest<'_, S> {
         pb::AuthDisableRequest::default().build(self)
     }
 
@@ -371,7 +373,7 @@ where
         Ok(())
     }
 
-    pub fn do_user_get(&mut self, name: impl Into<String>) -> DoAuthUserGetRequest<S> {
+    pub fn do_user_get(&mut self, name: impl Into<String>) -> DoAuthUserGetRequest<'_, S> {
         pb::AuthUserGetRequest::new(name.into()).build(self)
     }
 
@@ -385,6 +387,7 @@ impl pb::AuthEnableRequest {
         DoAuthEnableRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -392,11 +395,18 @@ impl pb::AuthEnableRequest {
 pub struct DoAuthEnableRequest<'a, S> {
     pub request: pb::AuthEnableRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthEnableRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -411,8 +421,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthEnableResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthEnableRequest { request, client } = self;
-        Box::pin(async move { client.auth_enable(request).await })
+        let DoAuthEnableRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.auth_enable(request)).await
+        })
     }
 }
 impl pb::AuthDisableRequest {
@@ -420,6 +437,7 @@ impl pb::AuthDisableRequest {
         DoAuthDisableRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -427,11 +445,18 @@ impl pb::AuthDisableRequest {
 pub struct DoAuthDisableRequest<'a, S> {
     pub request: pb::AuthDisableRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthDisableRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -446,8 +471,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthDisableResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthDisableRequest { request, client } = self;
-        Box::pin(async move { client.auth_disable(request).await })
+        let DoAuthDisableRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.auth_disable(request)).await
+        })
     }
 }
 impl pb::AuthStatusRequest {
@@ -455,6 +487,7 @@ impl pb::AuthStatusRequest {
         DoAuthStatusRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -462,11 +495,18 @@ impl pb::AuthStatusRequest {
 pub struct DoAuthStatusRequest<'a, S> {
     pub request: pb::AuthStatusRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthStatusRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -481,8 +521,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthStatusResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthStatusRequest { request, client } = self;
-        Box::pin(async move { client.auth_status(request).await })
+        let DoAuthStatusRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.auth_status(request)).await
+        })
     }
 }
 impl pb::AuthenticateRequest {
@@ -494,6 +541,7 @@ impl pb::AuthenticateRequest {
         DoAuthenticateRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -501,11 +549,18 @@ impl pb::AuthenticateRequest {
 pub struct DoAuthenticateRequest<'a, S> {
     pub request: pb::AuthenticateRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthenticateRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -528,8 +583,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthenticateResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthenticateRequest { request, client } = self;
-        Box::pin(async move { client.authenticate(request).await })
+        let DoAuthenticateRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.authenticate(request)).await
+        })
     }
 }
 impl pb::AuthUserAddRequest {
@@ -545,6 +607,7 @@ impl pb::AuthUserAddRequest {
         DoAuthUserAddRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -552,11 +615,18 @@ impl pb::AuthUserAddRequest {
 pub struct DoAuthUserAddRequest<'a, S> {
     pub request: pb::AuthUserAddRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthUserAddRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -583,8 +653,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthUserAddResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthUserAddRequest { request, client } = self;
-        Box::pin(async move { client.user_add(request).await })
+        let DoAuthUserAddRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.user_add(request)).await
+        })
     }
 }
 impl pb::AuthUserGetRequest {
@@ -596,6 +673,7 @@ impl pb::AuthUserGetRequest {
         DoAuthUserGetRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -603,11 +681,18 @@ impl pb::AuthUserGetRequest {
 pub struct DoAuthUserGetRequest<'a, S> {
     pub request: pb::AuthUserGetRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthUserGetRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -626,8 +711,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthUserGetResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthUserGetRequest { request, client } = self;
-        Box::pin(async move { client.user_get(request).await })
+        let DoAuthUserGetRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.user_get(request)).await
+        })
     }
 }
 impl pb::AuthUserListRequest {
@@ -635,6 +727,7 @@ impl pb::AuthUserListRequest {
         DoAuthUserListRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -642,11 +735,18 @@ impl pb::AuthUserListRequest {
 pub struct DoAuthUserListRequest<'a, S> {
     pub request: pb::AuthUserListRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthUserListRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -661,8 +761,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthUserListResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthUserListRequest { request, client } = self;
-        Box::pin(async move { client.user_list(request).await })
+        let DoAuthUserListRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.user_list(request)).await
+        })
     }
 }
 impl pb::AuthUserDeleteRequest {
@@ -673,6 +780,7 @@ impl pb::AuthUserDeleteRequest {
         DoAuthUserDeleteRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -680,11 +788,18 @@ impl pb::AuthUserDeleteRequest {
 pub struct DoAuthUserDeleteRequest<'a, S> {
     pub request: pb::AuthUserDeleteRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthUserDeleteRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -705,8 +820,15 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthUserDeleteRequest { request, client } = self;
-        Box::pin(async move { client.user_delete(request).await })
+        let DoAuthUserDeleteRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.user_delete(request)).await
+        })
     }
 }
 impl pb::AuthUserChangePasswordRequest {
@@ -717,6 +839,7 @@ impl pb::AuthUserChangePasswordRequest {
         DoAuthUserChangePasswordRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -724,11 +847,18 @@ impl pb::AuthUserChangePasswordRequest {
 pub struct DoAuthUserChangePasswordRequest<'a, S> {
     pub request: pb::AuthUserChangePasswordRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthUserChangePasswordRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -759,8 +889,15 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthUserChangePasswordRequest { request, client } = self;
-        Box::pin(async move { client.user_change_password(request).await })
+        let DoAuthUserChangePasswordRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.user_change_password(request)).await
+        })
     }
 }
 impl pb::AuthUserGrantRoleRequest {
@@ -771,6 +908,7 @@ impl pb::AuthUserGrantRoleRequest {
         DoAuthUserGrantRoleRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -778,11 +916,18 @@ impl pb::AuthUserGrantRoleRequest {
 pub struct DoAuthUserGrantRoleRequest<'a, S> {
     pub request: pb::AuthUserGrantRoleRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthUserGrantRoleRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -808,8 +953,15 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthUserGrantRoleRequest { request, client } = self;
-        Box::pin(async move { client.user_grant_role(request).await })
+        let DoAuthUserGrantRoleRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.user_grant_role(request)).await
+        })
     }
 }
 impl pb::AuthUserRevokeRoleRequest {
@@ -820,6 +972,7 @@ impl pb::AuthUserRevokeRoleRequest {
         DoAuthUserRevokeRoleRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -827,11 +980,18 @@ impl pb::AuthUserRevokeRoleRequest {
 pub struct DoAuthUserRevokeRoleRequest<'a, S> {
     pub request: pb::AuthUserRevokeRoleRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthUserRevokeRoleRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -857,8 +1017,15 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthUserRevokeRoleRequest { request, client } = self;
-        Box::pin(async move { client.user_revoke_role(request).await })
+        let DoAuthUserRevokeRoleRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.user_revoke_role(request)).await
+        })
     }
 }
 impl pb::AuthRoleAddRequest {
@@ -866,6 +1033,7 @@ impl pb::AuthRoleAddRequest {
         DoAuthRoleAddRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -873,11 +1041,18 @@ impl pb::AuthRoleAddRequest {
 pub struct DoAuthRoleAddRequest<'a, S> {
     pub request: pb::AuthRoleAddRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthRoleAddRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -896,8 +1071,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthRoleAddResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthRoleAddRequest { request, client } = self;
-        Box::pin(async move { client.role_add(request).await })
+        let DoAuthRoleAddRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.role_add(request)).await
+        })
     }
 }
 impl pb::AuthRoleGetRequest {
@@ -905,6 +1087,7 @@ impl pb::AuthRoleGetRequest {
         DoAuthRoleGetRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -912,11 +1095,18 @@ impl pb::AuthRoleGetRequest {
 pub struct DoAuthRoleGetRequest<'a, S> {
     pub request: pb::AuthRoleGetRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthRoleGetRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -935,8 +1125,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthRoleGetResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthRoleGetRequest { request, client } = self;
-        Box::pin(async move { client.role_get(request).await })
+        let DoAuthRoleGetRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.role_get(request)).await
+        })
     }
 }
 impl pb::AuthRoleListRequest {
@@ -944,6 +1141,7 @@ impl pb::AuthRoleListRequest {
         DoAuthRoleListRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -951,11 +1149,18 @@ impl pb::AuthRoleListRequest {
 pub struct DoAuthRoleListRequest<'a, S> {
     pub request: pb::AuthRoleListRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthRoleListRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -970,8 +1175,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::AuthRoleListResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthRoleListRequest { request, client } = self;
-        Box::pin(async move { client.role_list(request).await })
+        let DoAuthRoleListRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.role_list(request)).await
+        })
     }
 }
 impl pb::AuthRoleDeleteRequest {
@@ -982,6 +1194,7 @@ impl pb::AuthRoleDeleteRequest {
         DoAuthRoleDeleteRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -989,11 +1202,18 @@ impl pb::AuthRoleDeleteRequest {
 pub struct DoAuthRoleDeleteRequest<'a, S> {
     pub request: pb::AuthRoleDeleteRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthRoleDeleteRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -1014,8 +1234,15 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthRoleDeleteRequest { request, client } = self;
-        Box::pin(async move { client.role_delete(request).await })
+        let DoAuthRoleDeleteRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.role_delete(request)).await
+        })
     }
 }
 impl pb::AuthRoleGrantPermissionRequest {
@@ -1026,6 +1253,7 @@ impl pb::AuthRoleGrantPermissionRequest {
         DoAuthRoleGrantPermissionRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -1033,11 +1261,18 @@ impl pb::AuthRoleGrantPermissionRequest {
 pub struct DoAuthRoleGrantPermissionRequest<'a, S> {
     pub request: pb::AuthRoleGrantPermissionRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthRoleGrantPermissionRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -1060,8 +1295,15 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthRoleGrantPermissionRequest { request, client } = self;
-        Box::pin(async move { client.role_grant_permission(request).await })
+        let DoAuthRoleGrantPermissionRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.role_grant_permission(request)).await
+        })
     }
 }
 impl pb::AuthRoleRevokePermissionRequest {
@@ -1072,6 +1314,7 @@ impl pb::AuthRoleRevokePermissionRequest {
         DoAuthRoleRevokePermissionRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -1079,11 +1322,18 @@ impl pb::AuthRoleRevokePermissionRequest {
 pub struct DoAuthRoleRevokePermissionRequest<'a, S> {
     pub request: pb::AuthRoleRevokePermissionRequest,
     pub(crate) client: &'a mut AuthClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoAuthRoleRevokePermissionRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut AuthClient<S>) -> Self {
         self.client = client;
         self
@@ -1114,7 +1364,14 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoAuthRoleRevokePermissionRequest { request, client } = self;
-        Box::pin(async move { client.role_revoke_permission(request).await })
+        let DoAuthRoleRevokePermissionRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.role_revoke_permission(request)).await
+        })
     }
 }
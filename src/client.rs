@@ -1,13 +1,31 @@
 use crate::auth::{AuthClient, InnerAuthClient};
+use crate::cluster::ClusterClient;
+use crate::election::ElectionClient;
 use crate::error::{ErrKind, Error, Result};
-use crate::grpc::{CredentialInterceptor, GrpcService, TonicClient};
+use crate::grpc::{CredentialInterceptor, GrpcService, HttpService, TonicClient, box_service};
 use crate::kv::KvClient;
 use crate::lease::{LeaseClient, LeaseKeepAliver};
+use crate::lock::LockClient;
+use crate::maintenance::MaintenanceClient;
 use crate::pb;
 use crate::watch::{WatchClient, Watcher};
 
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
 use http::Uri;
-use tonic::transport::{Endpoint, channel::Channel};
+use prost::bytes::Bytes;
+use tokio::sync::mpsc;
+use tonic::body::Body;
+use tonic::transport::{
+    Endpoint,
+    channel::{Change, Channel},
+};
+use tower::{BoxError, Layer, Service};
+
+const DISCOVERY_CHANNEL_SIZE: usize = 16;
 
 pub type EtcdClient = Client<CredentialInterceptor<TonicClient>>;
 
@@ -17,6 +35,10 @@ pub struct Client<S> {
     pub auth: AuthClient<S>,
     pub watch: WatchClient<S>,
     pub lease: LeaseClient<S>,
+    pub cluster: ClusterClient<S>,
+    pub maintenance: MaintenanceClient<S>,
+    pub lock: LockClient<S>,
+    pub election: ElectionClient<S>,
 
     pub(crate) service: S,
 }
@@ -31,23 +53,520 @@ impl Client<CredentialInterceptor<TonicClient>> {
         Uri: TryFrom<U>,
         <Uri as TryFrom<U>>::Error: std::error::Error + std::marker::Send + Sync + 'static,
     {
-        let mut ep_uris = Vec::new();
+        let mut builder = Client::builder(endpoints);
+        if let Some((name, password)) = credential.into() {
+            builder = builder.with_credential(name, password);
+        }
+
+        builder.build().await
+    }
+
+    /// Update the endpoints from the client urls of the members.
+    ///
+    /// Requires the client is built with [`ClientBuilder::with_endpoint_sync`].
+    pub async fn sync_endpoints(&mut self) -> Result<()> {
+        let sync = self
+            .service
+            .inner()
+            .endpoint_sync()
+            .ok_or_else(|| Error::new(ErrKind::Endpoint, "endpoint sync is not enabled"))?;
+
+        sync_endpoints(&mut self.cluster, &sync).await
+    }
+
+    /// Create a ClientBuilder to configure the connections
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use etcdv3client::{Client, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// let client = Client::builder(vec!["http://localhost:2379"])
+    ///     .with_connect_timeout(Duration::from_secs(3))
+    ///     .with_keep_alive(Duration::from_secs(30), Duration::from_secs(10))
+    ///     .with_max_decoding_message_size(64 * 1024 * 1024)
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder<U>(endpoints: impl Into<Vec<U>>) -> ClientBuilder
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: std::error::Error + std::marker::Send + Sync + 'static,
+    {
+        ClientBuilder::new(endpoints)
+    }
+}
+
+/// The options of the connections to etcd.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientOptions {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) keep_alive_interval: Option<Duration>,
+    pub(crate) keep_alive_timeout: Option<Duration>,
+    pub(crate) keep_alive_while_idle: bool,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) tcp_nodelay: bool,
+    pub(crate) max_decoding_message_size: Option<usize>,
+    pub(crate) max_encoding_message_size: Option<usize>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TlsOptions>,
+    pub(crate) layer: Option<ServiceLayer>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            connect_timeout: None,
+            timeout: None,
+            keep_alive_interval: None,
+            keep_alive_timeout: None,
+            keep_alive_while_idle: false,
+            tcp_keepalive: None,
+            tcp_nodelay: true,
+            max_decoding_message_size: None,
+            max_encoding_message_size: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+            layer: None,
+        }
+    }
+}
+
+/// The tower layers wrapping the channels, see [`ClientBuilder::layer`].
+#[derive(Clone)]
+pub(crate) struct ServiceLayer(Arc<dyn Fn(HttpService) -> HttpService + Send + Sync>);
+
+impl fmt::Debug for ServiceLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceLayer").finish_non_exhaustive()
+    }
+}
+
+impl ClientOptions {
+    /// Wrap the channel with the layers.
+    pub(crate) fn layer(&self, channel: Channel) -> HttpService {
+        let service = box_service(channel);
+        match &self.layer {
+            Some(layer) => (layer.0)(service),
+            None => service,
+        }
+    }
+
+    fn endpoint(&self, uri: Uri) -> Result<Endpoint> {
+        let mut endpoint = Channel::builder(uri)
+            .tcp_nodelay(self.tcp_nodelay)
+            .tcp_keepalive(self.tcp_keepalive)
+            .keep_alive_while_idle(self.keep_alive_while_idle);
+
+        if let Some(timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        if let Some(interval) = self.keep_alive_interval {
+            endpoint = endpoint.http2_keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.keep_alive_timeout {
+            endpoint = endpoint.keep_alive_timeout(timeout);
+        }
+
+        #[cfg(feature = "tls")]
+        if self.tls.is_some() || endpoint.uri().scheme() == Some(&http::uri::Scheme::HTTPS) {
+            let tls = self.tls.clone().unwrap_or_default();
+            endpoint = endpoint
+                .tls_config(tls.to_config())
+                .map_err(|err| Error::new(ErrKind::Endpoint, err))?;
+        }
+
+        Ok(endpoint)
+    }
+
+    async fn new_channel(&self, endpoints: Vec<Uri>) -> Result<Channel> {
+        let mut eps: Vec<Endpoint> = Vec::new();
 
-        // check endpoints
-        let endpoints = endpoints.into();
         for ep in endpoints {
-            let uri = Uri::try_from(ep).map_err(|err| Error::new(ErrKind::Endpoint, err))?;
-            if uri.scheme().is_none() {
-                return Err(Error::new(ErrKind::Endpoint, "endpoint scheme is empty"));
+            eps.push(self.endpoint(ep)?);
+        }
+
+        match eps.len() {
+            0 => Err(Error::new(ErrKind::Endpoint, "endpoint uri empty")),
+            1 => eps[0]
+                .connect()
+                .await
+                .map_err(|err| Error::new(ErrKind::ConnectFailed, err)),
+            _ => Ok(Channel::balance_list(eps.into_iter())),
+        }
+    }
+
+    pub(crate) async fn connect_to(&self, endpoint: Uri) -> Result<Channel> {
+        self.endpoint(endpoint)?
+            .connect()
+            .await
+            .map_err(|err| Error::new(ErrKind::ConnectFailed, err))
+    }
+}
+
+/// The TLS options of the connections.
+///
+/// The native roots of the platform are trusted if no CA certificate is given.
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    ca_certificates: Vec<Vec<u8>>,
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+    domain_name: Option<String>,
+    native_roots: bool,
+}
+
+#[cfg(feature = "tls")]
+impl TlsOptions {
+    pub fn new() -> Self {
+        TlsOptions::default()
+    }
+
+    /// Trust the CA certificate in PEM.
+    pub fn with_ca_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.ca_certificates.push(pem.into());
+        self
+    }
+
+    /// Authenticate with the client certificate and key in PEM.
+    pub fn with_identity(mut self, cert: impl Into<Vec<u8>>, key: impl Into<Vec<u8>>) -> Self {
+        self.identity = Some((cert.into(), key.into()));
+        self
+    }
+
+    /// Verify the server certificate against `domain_name` instead of the endpoint host.
+    pub fn with_domain_name(mut self, domain_name: impl Into<String>) -> Self {
+        self.domain_name = Some(domain_name.into());
+        self
+    }
+
+    /// Trust the native roots of the platform along with the given CA certificates.
+    pub fn with_native_roots(mut self) -> Self {
+        self.native_roots = true;
+        self
+    }
+
+    fn to_config(&self) -> tonic::transport::ClientTlsConfig {
+        use tonic::transport::{Certificate, ClientTlsConfig, Identity};
+
+        let mut config = ClientTlsConfig::new()
+            .ca_certificates(self.ca_certificates.iter().map(Certificate::from_pem));
+        if self.native_roots || self.ca_certificates.is_empty() {
+            config = config.with_native_roots();
+        }
+        if let Some((cert, key)) = &self.identity {
+            config = config.identity(Identity::from_pem(cert, key));
+        }
+        if let Some(domain_name) = &self.domain_name {
+            config = config.domain_name(domain_name.clone());
+        }
+
+        config
+    }
+}
+
+/// The endpoints of a balanced channel, updated from the member list.
+#[derive(Debug)]
+pub(crate) struct EndpointSync {
+    options: Arc<ClientOptions>,
+    endpoints: tokio::sync::Mutex<HashSet<Uri>>,
+    tx: mpsc::Sender<Change<Uri, Endpoint>>,
+}
+
+impl EndpointSync {
+    async fn new(options: Arc<ClientOptions>, endpoints: Vec<Uri>) -> Result<(Channel, Self)> {
+        if endpoints.is_empty() {
+            return Err(Error::new(ErrKind::Endpoint, "endpoint uri empty"));
+        }
+
+        let (channel, tx) = Channel::balance_channel(DISCOVERY_CHANNEL_SIZE);
+        let sync = EndpointSync {
+            options,
+            endpoints: tokio::sync::Mutex::new(HashSet::new()),
+            tx,
+        };
+        sync.update(endpoints).await?;
+
+        Ok((channel, sync))
+    }
+
+    /// Replace the endpoints of the channel.
+    async fn update(&self, endpoints: Vec<Uri>) -> Result<()> {
+        let endpoints: HashSet<Uri> = endpoints.into_iter().collect();
+        let mut current = self.endpoints.lock().await;
+
+        let inserted: Vec<Uri> = endpoints.difference(&current).cloned().collect();
+        let removed: Vec<Uri> = current.difference(&endpoints).cloned().collect();
+
+        // `current` follows the changes sent, so a failed update is resumed by the next one.
+        for uri in inserted {
+            let endpoint = self.options.endpoint(uri.clone())?;
+            self.send(Change::Insert(uri.clone(), endpoint)).await?;
+            current.insert(uri);
+        }
+        for uri in removed {
+            self.send(Change::Remove(uri.clone())).await?;
+            current.remove(&uri);
+        }
+
+        tracing::debug!(?endpoints, "endpoints updated");
+
+        Ok(())
+    }
+
+    async fn send(&self, change: Change<Uri, Endpoint>) -> Result<()> {
+        self.tx
+            .send(change)
+            .await
+            .map_err(|_| Error::new(ErrKind::Endpoint, "balanced channel is closed"))
+    }
+}
+
+async fn sync_endpoints<S: GrpcService>(
+    cluster: &mut ClusterClient<S>,
+    sync: &EndpointSync,
+) -> Result<()> {
+    let members = cluster
+        .member_list(pb::MemberListRequest::new())
+        .await?
+        .members;
+
+    // like the Go client, learners and members which are not started yet are skipped.
+    let endpoints: Vec<Uri> = members
+        .iter()
+        .filter(|member| !member.is_learner && !member.name.is_empty())
+        .flat_map(|member| member.client_ur_ls.iter())
+        .filter_map(|url| match Uri::try_from(url.as_str()) {
+            Ok(uri) => Some(uri),
+            Err(err) => {
+                tracing::warn!(?err, url, "invalid member client url");
+                None
             }
-            ep_uris.push(uri);
+        })
+        .collect();
+
+    if endpoints.is_empty() {
+        return Err(Error::new(ErrKind::Endpoint, "no member client urls"));
+    }
+
+    sync.update(endpoints).await
+}
+
+/// Sync the endpoints every `interval`, until all clients are dropped.
+async fn auto_sync<S: GrpcService>(
+    mut cluster: ClusterClient<S>,
+    sync: Weak<EndpointSync>,
+    interval: Duration,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let Some(sync) = sync.upgrade() else {
+            return;
+        };
+        if let Err(err) = sync_endpoints(&mut cluster, &sync).await {
+            tracing::warn!(?err, "auto sync endpoints failed");
         }
+    }
+}
+
+/// A builder to configure the connections of a [`Client`].
+#[derive(Debug)]
+pub struct ClientBuilder {
+    endpoints: Result<Vec<Uri>>,
+    credential: Option<(String, String)>,
+    options: ClientOptions,
+    endpoint_sync: bool,
+    auto_sync_interval: Option<Duration>,
+}
+
+impl ClientBuilder {
+    pub fn new<U>(endpoints: impl Into<Vec<U>>) -> Self
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: std::error::Error + std::marker::Send + Sync + 'static,
+    {
+        // check endpoints
+        let endpoints = endpoints
+            .into()
+            .into_iter()
+            .map(|ep| {
+                let uri = Uri::try_from(ep).map_err(|err| Error::new(ErrKind::Endpoint, err))?;
+                if uri.scheme().is_none() {
+                    return Err(Error::new(ErrKind::Endpoint, "endpoint scheme is empty"));
+                }
+                Ok(uri)
+            })
+            .collect();
+
+        ClientBuilder {
+            endpoints,
+            credential: None,
+            options: ClientOptions::default(),
+            endpoint_sync: false,
+            auto_sync_interval: None,
+        }
+    }
+
+    /// Authenticate with user name and password.
+    pub fn with_credential(mut self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credential = Some((name.into(), password.into()));
+        self
+    }
+
+    /// Set the timeout of connecting to an endpoint.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the default timeout of each unary request, `with_timeout` of the request overrides it.
+    ///
+    /// The timeout is sent to the server as the deadline of the request, and a request timed out
+    /// fails with `ErrKind::DeadlineExceeded`.
+    ///
+    /// It does not apply to the requests blocking until acquired, like `LockClient::lock` and
+    /// `ElectionClient::campaign`, only `with_timeout` of those requests does.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Send HTTP/2 pings every `interval`, the connection is closed if a ping is not
+    /// acknowledged within `timeout`.
+    pub fn with_keep_alive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.options.keep_alive_interval = Some(interval);
+        self.options.keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    /// Send HTTP/2 pings even if there are no active streams.
+    pub fn with_keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.options.keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// Set the TCP keepalive of the connections.
+    pub fn with_tcp_keepalive(mut self, keepalive: Option<Duration>) -> Self {
+        self.options.tcp_keepalive = keepalive;
+        self
+    }
+
+    /// Set `TCP_NODELAY` of the connections, enabled by default.
+    pub fn with_tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.options.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Set the max size of a decoded message, 4MiB by default.
+    pub fn with_max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.options.max_decoding_message_size = Some(limit);
+        self
+    }
+
+    /// Set the max size of an encoded message, unlimited by default.
+    pub fn with_max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.options.max_encoding_message_size = Some(limit);
+        self
+    }
+
+    /// Set the TLS options of `https` endpoints.
+    ///
+    /// If etcd runs with `--client-cert-auth`, the CommonName of the client certificate is
+    /// taken as the user name, so no credential is needed.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{Client, Error, TlsOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// let tls = TlsOptions::new()
+    ///     .with_ca_certificate(std::fs::read("ca.pem").unwrap())
+    ///     .with_identity(
+    ///         std::fs::read("client.pem").unwrap(),
+    ///         std::fs::read("client-key.pem").unwrap(),
+    ///     );
+    ///
+    /// let client = Client::builder(vec!["https://localhost:2379"])
+    ///     .with_tls(tls)
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: TlsOptions) -> Self {
+        self.options.tls = Some(tls);
+        self
+    }
+
+    /// Balance the requests over the endpoints updated from the member list, see
+    /// [`Client::sync_endpoints`].
+    pub fn with_endpoint_sync(mut self) -> Self {
+        self.endpoint_sync = true;
+        self
+    }
+
+    /// Update the endpoints from the member list every `interval`, this enables endpoint sync.
+    pub fn with_auto_sync_interval(mut self, interval: Duration) -> Self {
+        self.endpoint_sync = true;
+        self.auto_sync_interval = Some(interval);
+        self
+    }
+
+    /// Wrap the connections with a tower layer, like concurrency limits, rate limits or load
+    /// shedding. All requests go through the layer, including the streams of watch and lease
+    /// keep alive, and the layer added later wraps the earlier ones.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{Client, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// let client = Client::builder(vec!["http://localhost:2379"])
+    ///     .layer(tower::limit::ConcurrencyLimitLayer::new(64))
+    ///     .layer(tower::load_shed::LoadShedLayer::new())
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn layer<L, B>(mut self, layer: L) -> Self
+    where
+        L: Layer<HttpService> + Send + Sync + 'static,
+        L::Service: Service<http::Request<Body>, Response = http::Response<B>>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <L::Service as Service<http::Request<Body>>>::Future: Send + 'static,
+        <L::Service as Service<http::Request<Body>>>::Error: Into<BoxError>,
+        B: http_body::Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        let inner = self.options.layer.take();
+        let layer = move |service| {
+            let service = match &inner {
+                Some(inner) => (inner.0)(service),
+                None => service,
+            };
+            box_service(layer.layer(service))
+        };
+        self.options.layer = Some(ServiceLayer(Arc::new(layer)));
+        self
+    }
+
+    /// Connect to the endpoints and build the client.
+    pub async fn build(self) -> Result<EtcdClient> {
+        let ep_uris = self.endpoints?;
+        let options = Arc::new(self.options);
 
         // try to get token
-        let credential = credential.into();
+        let credential = self.credential;
         let token = match &credential {
             Some((name, password)) => {
-                let token = get_token(&ep_uris, name, password).await?;
+                let token = get_token(&options, &ep_uris, name, password).await?;
                 let token = token
                     .try_into()
                     .map_err(|err| Error::new(ErrKind::AuthFailed, err))?;
@@ -56,8 +575,29 @@ impl Client<CredentialInterceptor<TonicClient>> {
             None => None,
         };
 
-        let channel = new_channel(ep_uris).await?;
-        let service = CredentialInterceptor::new(credential, token, TonicClient::new(channel));
+        let (channel, endpoint_sync) = if self.endpoint_sync {
+            let (channel, sync) = EndpointSync::new(options.clone(), ep_uris).await?;
+            (channel, Some(Arc::new(sync)))
+        } else {
+            (options.new_channel(ep_uris).await?, None)
+        };
+
+        let service = CredentialInterceptor::new(
+            credential,
+            token,
+            TonicClient::with_options(channel.clone(), options.clone())
+                .with_endpoint_sync(endpoint_sync.clone()),
+        );
+
+        if let (Some(interval), Some(sync)) = (self.auto_sync_interval, &endpoint_sync) {
+            // the sync task must not keep the endpoint sync alive.
+            let service = service.with_inner(TonicClient::with_options(channel, options));
+            tokio::spawn(auto_sync(
+                ClusterClient::new(service),
+                Arc::downgrade(sync),
+                interval,
+            ));
+        }
 
         Ok(Client::with_service(service))
     }
@@ -74,6 +614,10 @@ impl<S: GrpcService> Client<S> {
             kv: KvClient::new(service.clone()),
             watch: WatchClient::new(service.clone()),
             lease: LeaseClient::new(service.clone()),
+            cluster: ClusterClient::new(service.clone()),
+            maintenance: MaintenanceClient::new(service.clone()),
+            lock: LockClient::new(service.clone()),
+            election: ElectionClient::new(service.clone()),
             service,
         }
     }
@@ -157,6 +701,11 @@ impl<S: GrpcService> Client<S> {
         self.lease.list().await
     }
 
+    /// List all cluster members
+    pub async fn list_members(&mut self) -> Result<Vec<pb::Member>> {
+        self.cluster.list_members().await
+    }
+
     // /// Refresh token
     // pub async fn refresh_token(&mut self) -> Result<()> {
     //     if let Some((username, password)) = &self.credential {
@@ -169,11 +718,17 @@ impl<S: GrpcService> Client<S> {
     // }
 }
 
-async fn get_token(endpoints: &[Uri], name: &str, password: &str) -> Result<String> {
+async fn get_token(
+    options: &Arc<ClientOptions>,
+    endpoints: &[Uri],
+    name: &str,
+    password: &str,
+) -> Result<String> {
     for ep in endpoints {
-        let channel = connect_to(ep.to_owned()).await?;
+        let channel = options.connect_to(ep.to_owned()).await?;
 
-        let mut auth_client = InnerAuthClient::new(TonicClient::new(channel));
+        let mut auth_client =
+            InnerAuthClient::new(TonicClient::with_options(channel, options.clone()));
 
         match auth_client
             .get_token(name.to_string(), password.to_string())
@@ -194,26 +749,107 @@ async fn get_token(endpoints: &[Uri], name: &str, password: &str) -> Result<Stri
     Err(Error::new(ErrKind::AuthFailed, "all endpoints failed"))
 }
 
-async fn new_channel(endpoints: Vec<Uri>) -> Result<Channel> {
-    let mut eps: Vec<Endpoint> = Vec::new();
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
 
-    for ep in endpoints {
-        eps.push(Channel::builder(ep));
-    }
+    use http::Uri;
+    use tokio::sync::mpsc;
+    use tonic::transport::channel::Change;
 
-    match eps.len() {
-        0 => Err(Error::new(ErrKind::Endpoint, "endpoint uri empty")),
-        1 => eps[0]
-            .connect()
+    use super::{ClientOptions, EndpointSync, sync_endpoints};
+    use crate::cluster::ClusterClient;
+    use crate::grpc::StubService;
+    use crate::pb;
+
+    #[tokio::test]
+    async fn test_endpoint_sync_update() {
+        let uri = |s: &str| Uri::try_from(s).unwrap();
+        let options = Arc::new(ClientOptions::default());
+
+        let (_channel, mut sync) =
+            EndpointSync::new(options, vec![uri("http://a:2379"), uri("http://b:2379")])
+                .await
+                .unwrap();
+
+        // observe the changes sent to the balanced channel.
+        let (tx, mut rx) = mpsc::channel(16);
+        sync.tx = tx;
+
+        sync.update(vec![uri("http://b:2379"), uri("http://c:2379")])
+            .await
+            .unwrap();
+
+        let mut inserted = Vec::new();
+        let mut removed = Vec::new();
+        while let Ok(change) = rx.try_recv() {
+            match change {
+                Change::Insert(uri, _) => inserted.push(uri),
+                Change::Remove(uri) => removed.push(uri),
+            }
+        }
+        assert_eq!(inserted, vec![uri("http://c:2379")]);
+        assert_eq!(removed, vec![uri("http://a:2379")]);
+
+        // nothing is sent if the endpoints are unchanged.
+        sync.update(vec![uri("http://c:2379"), uri("http://b:2379")])
             .await
-            .map_err(|err| Error::new(ErrKind::ConnectFailed, err)),
-        _ => Ok(Channel::balance_list(eps.into_iter())),
+            .unwrap();
+        assert!(rx.try_recv().is_err());
+
+        // a failed update is sent again by the next one.
+        drop(rx);
+        let endpoints = vec![uri("http://c:2379"), uri("http://d:2379")];
+        assert!(sync.update(endpoints.clone()).await.is_err());
+
+        let (tx, mut rx) = mpsc::channel(16);
+        sync.tx = tx;
+        sync.update(endpoints).await.unwrap();
+        let mut changes = Vec::new();
+        while let Ok(change) = rx.try_recv() {
+            changes.push(change);
+        }
+        assert!(
+            matches!(&changes[..], [Change::Insert(uri, _), Change::Remove(_)] if uri == "http://d:2379")
+        );
     }
-}
 
-async fn connect_to(endpoint: Uri) -> Result<Channel> {
-    Channel::builder(endpoint)
-        .connect()
-        .await
-        .map_err(|err| Error::new(ErrKind::ConnectFailed, err))
+    #[tokio::test]
+    async fn test_sync_endpoints() {
+        let uri = |s: &str| Uri::try_from(s).unwrap();
+        let member = |name: &str, url: &str, is_learner: bool| pb::Member {
+            name: name.to_string(),
+            client_ur_ls: vec![url.to_string()],
+            is_learner,
+            ..Default::default()
+        };
+
+        let service = StubService::default();
+        service.respond(pb::MemberListResponse {
+            members: vec![
+                member("a", "http://a:2379", false),
+                member("b", "http://b:2379", true),
+                member("", "http://c:2379", false),
+            ],
+            ..Default::default()
+        });
+
+        let options = Arc::new(ClientOptions::default());
+        let (_channel, mut sync) = EndpointSync::new(options, vec![uri("http://x:2379")])
+            .await
+            .unwrap();
+        let (tx, mut rx) = mpsc::channel(16);
+        sync.tx = tx;
+
+        let mut cluster = ClusterClient::new(service);
+        sync_endpoints(&mut cluster, &sync).await.unwrap();
+
+        let mut inserted = Vec::new();
+        while let Ok(change) = rx.try_recv() {
+            if let Change::Insert(uri, _) = change {
+                inserted.push(uri);
+            }
+        }
+        assert_eq!(inserted, vec![uri("http://a:2379")]);
+    }
 }
@@ -0,0 +1,526 @@
+use crate::error::Result;
+use crate::grpc::GrpcService;
+use crate::pb;
+use crate::utils::{new_request, with_deadline};
+
+use std::time::Duration;
+use tonic::IntoRequest;
+
+#[derive(Debug, Clone)]
+pub struct InnerClusterClient<S> {
+    service: S,
+}
+impl<S> InnerClusterClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+    pub async fn member_add(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::MemberAddRequest>,
+    ) -> Result<tonic::Response<pb::MemberAddResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Cluster/MemberAdd");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn member_remove(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::MemberRemoveRequest>,
+    ) -> Result<tonic::Response<pb::MemberRemoveResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Cluster/MemberRemove");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn member_update(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::MemberUpdateRequest>,
+    ) -> Result<tonic::Response<pb::MemberUpdateResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Cluster/MemberUpdate");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn member_list(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::MemberListRequest>,
+    ) -> Result<tonic::Response<pb::MemberListResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Cluster/MemberList");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn member_promote(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::MemberPromoteRequest>,
+    ) -> Result<tonic::Response<pb::MemberPromoteResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Cluster/MemberPromote");
+        self.service.unary(request.into_request(), path).await
+    }
+}
+#[derive(Debug, Clone)]
+pub struct ClusterClient<S> {
+    inner: InnerClusterClient<S>,
+}
+impl<S> ClusterClient<S>
+where
+    S: GrpcService,
+{
+    pub async fn member_add(
+        &mut self,
+        request: pb::MemberAddRequest,
+    ) -> Result<pb::MemberAddResponse> {
+        self.inner
+            .member_add(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn member_remove(
+        &mut self,
+        request: pb::MemberRemoveRequest,
+    ) -> Result<pb::MemberRemoveResponse> {
+        self.inner
+            .member_remove(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn member_update(
+        &mut self,
+        request: pb::MemberUpdateRequest,
+    ) -> Result<pb::MemberUpdateResponse> {
+        self.inner
+            .member_update(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn member_list(
+        &mut self,
+        request: pb::MemberListRequest,
+    ) -> Result<pb::MemberListResponse> {
+        self.inner
+            .member_list(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn member_promote(
+        &mut self,
+        request: pb::MemberPromoteRequest,
+    ) -> Result<pb::MemberPromoteResponse> {
+        self.inner
+            .member_promote(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+}
+
+impl<S> ClusterClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        ClusterClient {
+            inner: InnerClusterClient::new(service),
+        }
+    }
+
+    /// Do member add request
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error, ClusterClient};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let resp = ClusterClient::new(client.service())
+    ///     .do_member_add(vec!["http://10.0.0.4:2380".to_string()])
+    ///     .with_is_learner(true)
+    ///     .await
+    ///     .unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn do_member_add(
+        &mut self,
+        peer_urls: impl Into<Vec<String>>,
+    ) -> DoMemberAddRequest<'_, S> {
+        pb::MemberAddRequest::new(peer_urls, false).build(self)
+    }
+
+    /// Add a voting member with the given peer urls.
+    pub async fn add_member(&mut self, peer_urls: impl Into<Vec<String>>) -> Result<pb::Member> {
+        let resp = self.do_member_add(peer_urls).await?;
+        Ok(resp.member.unwrap_or_default())
+    }
+
+    /// Add a learner member with the given peer urls.
+    pub async fn add_learner(&mut self, peer_urls: impl Into<Vec<String>>) -> Result<pb::Member> {
+        let resp = self.do_member_add(peer_urls).with_is_learner(true).await?;
+        Ok(resp.member.unwrap_or_default())
+    }
+
+    pub fn do_member_remove(&mut self, id: u64) -> DoMemberRemoveRequest<'_, S> {
+        pb::MemberRemoveRequest::new(id).build(self)
+    }
+
+    /// Remove the member with the given id.
+    pub async fn remove_member(&mut self, id: u64) -> Result<Vec<pb::Member>> {
+        let resp = self.do_member_remove(id).await?;
+        Ok(resp.members)
+    }
+
+    pub fn do_member_update(
+        &mut self,
+        id: u64,
+        peer_urls: impl Into<Vec<String>>,
+    ) -> DoMemberUpdateRequest<'_, S> {
+        pb::MemberUpdateRequest::new(id, peer_urls).build(self)
+    }
+
+    /// Update the peer urls of the member with the given id.
+    pub async fn update_member(
+        &mut self,
+        id: u64,
+        peer_urls: impl Into<Vec<String>>,
+    ) -> Result<Vec<pb::Member>> {
+        let resp = self.do_member_update(id, peer_urls).await?;
+        Ok(resp.members)
+    }
+
+    pub fn do_member_list(&mut self) -> DoMemberListRequest<'_, S> {
+        pb::MemberListRequest::new().build(self)
+    }
+
+    /// List all members of the cluster.
+    pub async fn list_members(&mut self) -> Result<Vec<pb::Member>> {
+        let resp = self.do_member_list().await?;
+        Ok(resp.members)
+    }
+
+    pub fn do_member_promote(&mut self, id: u64) -> DoMemberPromoteRequest<'_, S> {
+        pb::MemberPromoteRequest::new(id).build(self)
+    }
+
+    /// Promote the learner member with the given id to a voting member.
+    pub async fn promote_learner(&mut self, id: u64) -> Result<Vec<pb::Member>> {
+        let resp = self.do_member_promote(id).await?;
+        Ok(resp.members)
+    }
+}
+
+impl pb::MemberAddRequest {
+    pub fn new(peer_urls: impl Into<Vec<String>>, is_learner: bool) -> Self {
+        pb::MemberAddRequest {
+            peer_ur_ls: peer_urls.into(),
+            is_learner,
+        }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut ClusterClient<S>) -> DoMemberAddRequest<'_, S> {
+        DoMemberAddRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoMemberAddRequest<'a, S> {
+    pub request: pb::MemberAddRequest,
+    pub(crate) client: &'a mut ClusterClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoMemberAddRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ClusterClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_peer_urls(mut self, peer_urls: Vec<String>) -> Self {
+        self.request.peer_ur_ls = peer_urls;
+        self
+    }
+    pub fn with_is_learner(mut self, is_learner: bool) -> Self {
+        self.request.is_learner = is_learner;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoMemberAddRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::MemberAddResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::MemberAddResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoMemberAddRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.member_add(request)).await
+        })
+    }
+}
+
+impl pb::MemberRemoveRequest {
+    pub fn new(id: u64) -> Self {
+        pb::MemberRemoveRequest { id }
+    }
+
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut ClusterClient<S>,
+    ) -> DoMemberRemoveRequest<'_, S> {
+        DoMemberRemoveRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoMemberRemoveRequest<'a, S> {
+    pub request: pb::MemberRemoveRequest,
+    pub(crate) client: &'a mut ClusterClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoMemberRemoveRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ClusterClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.request.id = id;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoMemberRemoveRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::MemberRemoveResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::MemberRemoveResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoMemberRemoveRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.member_remove(request)).await
+        })
+    }
+}
+
+impl pb::MemberUpdateRequest {
+    pub fn new(id: u64, peer_urls: impl Into<Vec<String>>) -> Self {
+        pb::MemberUpdateRequest {
+            id,
+            peer_ur_ls: peer_urls.into(),
+        }
+    }
+
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut ClusterClient<S>,
+    ) -> DoMemberUpdateRequest<'_, S> {
+        DoMemberUpdateRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoMemberUpdateRequest<'a, S> {
+    pub request: pb::MemberUpdateRequest,
+    pub(crate) client: &'a mut ClusterClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoMemberUpdateRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ClusterClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.request.id = id;
+        self
+    }
+    pub fn with_peer_urls(mut self, peer_urls: Vec<String>) -> Self {
+        self.request.peer_ur_ls = peer_urls;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoMemberUpdateRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::MemberUpdateResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::MemberUpdateResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoMemberUpdateRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.member_update(request)).await
+        })
+    }
+}
+
+impl pb::MemberListRequest {
+    pub fn new() -> Self {
+        pb::MemberListRequest {
+            ..Default::default()
+        }
+    }
+
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut ClusterClient<S>,
+    ) -> DoMemberListRequest<'_, S> {
+        DoMemberListRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoMemberListRequest<'a, S> {
+    pub request: pb::MemberListRequest,
+    pub(crate) client: &'a mut ClusterClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoMemberListRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ClusterClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    /// When set, the member list is served by the leader rather than the local member.
+    pub fn with_linearizable(mut self, linearizable: bool) -> Self {
+        self.request.linearizable = linearizable;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoMemberListRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::MemberListResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::MemberListResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoMemberListRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.member_list(request)).await
+        })
+    }
+}
+
+impl pb::MemberPromoteRequest {
+    pub fn new(id: u64) -> Self {
+        pb::MemberPromoteRequest { id }
+    }
+
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut ClusterClient<S>,
+    ) -> DoMemberPromoteRequest<'_, S> {
+        DoMemberPromoteRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoMemberPromoteRequest<'a, S> {
+    pub request: pb::MemberPromoteRequest,
+    pub(crate) client: &'a mut ClusterClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoMemberPromoteRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ClusterClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.request.id = id;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoMemberPromoteRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::MemberPromoteResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::MemberPromoteResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoMemberPromoteRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.member_promote(request)).await
+        })
+    }
+}
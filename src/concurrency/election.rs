@@ -0,0 +1,344 @@
+use futures::Stream;
+
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::GrpcService;
+use crate::kv::KvClient;
+use crate::pb;
+use crate::watch::{WatchClient, Watcher};
+
+use super::{Session, first_create_request, range_response, wait_deletes};
+
+/// A leader election.
+///
+/// Every candidate puts a key `{prefix}/{lease_id:x}` bound to its session lease, the candidate
+/// whose key has the lowest create revision is the leader, and the value of its key is the
+/// leader value.
+///
+/// ```no_run
+/// # use etcdv3client::{EtcdClient, Error, concurrency::{Election, Session}};
+/// # use futures::StreamExt;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let session = Session::new(&client, 10).await?;
+/// let mut election = Election::new(&session, "/my-election");
+///
+/// let mut observer = Box::pin(election.observe());
+/// tokio::spawn(async move {
+///     while let Some(Ok(leader)) = observer.next().await {
+///         println!("leader is {:?}", String::from_utf8_lossy(&leader.value));
+///     }
+/// });
+///
+/// election.campaign("node-1").await?;
+/// // do something as the leader
+/// election.resign().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Election<S> {
+    kv: KvClient<S>,
+    watch: WatchClient<S>,
+    prefix: Vec<u8>,
+    lease_id: i64,
+    leader: Option<(Vec<u8>, i64)>,
+    header: Option<pb::ResponseHeader>,
+}
+
+impl<S> Election<S>
+where
+    S: GrpcService,
+{
+    /// Create an election under `prefix`, the candidate key is attached to the session lease.
+    pub fn new(session: &Session<S>, prefix: impl Into<Vec<u8>>) -> Self
+    where
+        S: 'static,
+    {
+        let mut prefix = prefix.into();
+        prefix.push(b'/');
+
+        let client = session.client();
+
+        Election {
+            kv: client.kv.clone(),
+            watch: client.watch.clone(),
+            prefix,
+            lease_id: session.lease_id(),
+            leader: None,
+            header: None,
+        }
+    }
+
+    /// The candidate key of this election.
+    pub fn key(&self) -> Vec<u8> {
+        let mut key = self.prefix.clone();
+        key.extend_from_slice(format!("{:x}", self.lease_id).as_bytes());
+        key
+    }
+
+    /// The create revision of the candidate key, if this candidate is elected.
+    pub fn revision(&self) -> Option<i64> {
+        self.leader.as_ref().map(|(_, revision)| *revision)
+    }
+
+    /// The response header of the last successful campaign, proclaim or resign.
+    pub fn header(&self) -> Option<&pb::ResponseHeader> {
+        self.header.as_ref()
+    }
+
+    /// Put the candidate key with `value`, and wait until this candidate is elected.
+    ///
+    /// The candidate key is deleted if waiting fails.
+    /// If the returned future is dropped before completion, the candidate key is kept until the
+    /// session lease expires.
+    pub async fn campaign(&mut self, value: impl Into<Vec<u8>>) -> Result<()> {
+        let key = self.key();
+        let value = value.into();
+
+        let cmp = pb::Compare::new(
+            key.clone(),
+            pb::compare::CompareResult::Equal,
+            pb::compare::TargetUnion::CreateRevision(0),
+        );
+        let put = pb::PutRequest {
+            lease: self.lease_id,
+            ..pb::PutRequest::new(key.clone(), value.clone())
+        };
+        let get = pb::RangeRequest::new(key.clone());
+
+        let resp = self
+            .kv
+            .do_txn()
+            .with_if(vec![cmp])
+            .with_then(vec![put.into()])
+            .with_else(vec![get.into()])
+            .await?;
+
+        if resp.succeeded {
+            let revision = resp.header.as_ref().map(|h| h.revision).unwrap_or_default();
+            self.leader = Some((key, revision));
+        } else {
+            let kv = range_response(&resp, 0)
+                .and_then(|r| r.kvs.first())
+                .ok_or_else(|| Error::new(ErrKind::InvalidData, "candidate key not found"))?;
+            self.leader = Some((key, kv.create_revision));
+
+            let proclaimed = if kv.value != value {
+                self.proclaim(value).await
+            } else {
+                Ok(())
+            };
+            if let Err(err) = proclaimed {
+                let _ = self.resign().await;
+                return Err(err);
+            }
+        }
+
+        let revision = self.revision().unwrap_or_default();
+        if let Err(err) =
+            wait_deletes(&mut self.kv, &mut self.watch, &self.prefix, revision - 1).await
+        {
+            // delete the candidate key, otherwise it becomes the leader while the lease lives.
+            let _ = self.resign().await;
+            return Err(err);
+        }
+
+        self.header = resp.header;
+
+        Ok(())
+    }
+
+    /// Update the leader value without another election.
+    ///
+    /// Returns `ErrKind::ElectionNotLeader` error if this candidate is not the leader anymore.
+    pub async fn proclaim(&mut self, value: impl Into<Vec<u8>>) -> Result<()> {
+        let (key, revision) = self
+            .leader
+            .clone()
+            .ok_or_else(|| Error::from_kind(ErrKind::ElectionNotLeader))?;
+
+        let cmp = pb::Compare::new(
+            key.clone(),
+            pb::compare::CompareResult::Equal,
+            pb::compare::TargetUnion::CreateRevision(revision),
+        );
+        let put = pb::PutRequest {
+            lease: self.lease_id,
+            ..pb::PutRequest::new(key, value)
+        };
+
+        let resp = self
+            .kv
+            .do_txn()
+            .with_if(vec![cmp])
+            .with_then(vec![put.into()])
+            .await?;
+
+        if !resp.succeeded {
+            self.leader = None;
+            return Err(Error::from_kind(ErrKind::ElectionNotLeader));
+        }
+
+        self.header = resp.header;
+
+        Ok(())
+    }
+
+    /// Give up the leadership, delete the candidate key.
+    pub async fn resign(&mut self) -> Result<()> {
+        let Some((key, revision)) = self.leader.take() else {
+            return Ok(());
+        };
+
+        let cmp = pb::Compare::new(
+            key.clone(),
+            pb::compare::CompareResult::Equal,
+            pb::compare::TargetUnion::CreateRevision(revision),
+        );
+        let delete = pb::DeleteRangeRequest::new(key);
+
+        let resp = self
+            .kv
+            .do_txn()
+            .with_if(vec![cmp])
+            .with_then(vec![delete.into()])
+            .await?;
+
+        self.header = resp.header;
+
+        Ok(())
+    }
+
+    /// Get the current leader key value.
+    ///
+    /// Returns `ErrKind::ElectionNoLeader` error if there is no candidate.
+    pub async fn leader(&mut self) -> Result<pb::KeyValue> {
+        let request =
+            first_create_request(self.prefix.clone(), pb::range_request::SortOrder::Ascend);
+        let resp = self.kv.range(request).await?;
+
+        resp.kvs
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::from_kind(ErrKind::ElectionNoLeader))
+    }
+
+    /// Observe the leader, yields the leader key value every time the leader or its value changes.
+    ///
+    /// The stream ends after yielding an error.
+    pub fn observe(&self) -> impl Stream<Item = Result<pb::KeyValue>> + use<S> {
+        let observer = Observer {
+            kv: self.kv.clone(),
+            watch: self.watch.clone(),
+            prefix: self.prefix.clone(),
+            watcher: None,
+        };
+
+        futures::stream::unfold(Some(observer), |observer| async move {
+            let mut observer = observer?;
+            match observer.next().await {
+                Ok(kv) => Some((Ok(kv), Some(observer))),
+                Err(err) => Some((Err(err), None)),
+            }
+        })
+    }
+}
+
+struct Observer<S> {
+    kv: KvClient<S>,
+    watch: WatchClient<S>,
+    prefix: Vec<u8>,
+    watcher: Option<Watcher>,
+}
+
+impl<S> Observer<S>
+where
+    S: GrpcService,
+{
+    async fn next(&mut self) -> Result<pb::KeyValue> {
+        loop {
+            let Some(watcher) = self.watcher.as_mut() else {
+                let leader = self.wait_leader().await?;
+
+                let watcher = self
+                    .watch
+                    .do_watch(leader.key.clone())
+                    .with_start_revision(leader.mod_revision + 1)
+                    .await?;
+                self.watcher = Some(watcher);
+
+                return Ok(leader);
+            };
+
+            let resp = watch_message(watcher).await?;
+
+            let mut leader = None;
+            let mut deleted = false;
+            for ev in resp.events {
+                if ev.r#type == pb::event::EventType::Delete as i32 {
+                    deleted = true;
+                    break;
+                }
+                leader = ev.kv;
+            }
+
+            if deleted {
+                let _ = watcher.cancel().await;
+                self.watcher = None;
+                continue;
+            }
+
+            if let Some(leader) = leader {
+                return Ok(leader);
+            }
+        }
+    }
+
+    /// Get the leader, wait for the first candidate if there is no one.
+    async fn wait_leader(&mut self) -> Result<pb::KeyValue> {
+        let request =
+            first_create_request(self.prefix.clone(), pb::range_request::SortOrder::Ascend);
+        let resp = self.kv.range(request).await?;
+
+        if let Some(leader) = resp.kvs.into_iter().next() {
+            return Ok(leader);
+        }
+
+        let revision = resp.header.map(|h| h.revision).unwrap_or_default();
+        let mut watcher = self
+            .watch
+            .do_watch(self.prefix.clone())
+            .with_prefix()
+            .with_start_revision(revision + 1)
+            .await?;
+
+        loop {
+            let resp = watch_message(&mut watcher).await?;
+
+            let leader = resp
+                .events
+                .into_iter()
+                .find(|ev| ev.r#type == pb::event::EventType::Put as i32)
+                .and_then(|ev| ev.kv);
+
+            if let Some(leader) = leader {
+                let _ = watcher.cancel().await;
+                return Ok(leader);
+            }
+        }
+    }
+}
+
+async fn watch_message(watcher: &mut Watcher) -> Result<pb::WatchResponse> {
+    let resp = watcher
+        .message()
+        .await?
+        .ok_or_else(|| Error::new(ErrKind::WatchFinished, "lost watcher observing leader"))?;
+
+    if resp.canceled {
+        return Err(Error::new(ErrKind::WatchCanceled, resp.cancel_reason));
+    }
+
+    Ok(resp)
+}
@@ -0,0 +1,88 @@
+//! Concurrency primitives built on top of etcd, like `clientv3/concurrency` of the Go client.
+
+mod election;
+mod mutex;
+mod session;
+mod stm;
+
+pub use election::Election;
+pub use mutex::{Mutex, MutexGuard};
+pub use session::Session;
+pub use stm::{Isolation, Stm, StmTxn};
+
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::GrpcService;
+use crate::kv::KvClient;
+use crate::pb;
+use crate::watch::WatchClient;
+
+/// Build a range request for the first (or last) created key under `prefix`.
+pub(crate) fn first_create_request(
+    prefix: impl Into<Vec<u8>>,
+    order: pb::range_request::SortOrder,
+) -> pb::RangeRequest {
+    pb::RangeRequest {
+        limit: 1,
+        sort_order: order.into(),
+        sort_target: pb::range_request::SortTarget::Create.into(),
+        ..pb::RangeRequest::new(prefix).with_prefix()
+    }
+}
+
+/// Get the range response of a txn response op.
+pub(crate) fn range_response(resp: &pb::TxnResponse, index: usize) -> Option<&pb::RangeResponse> {
+    resp.responses.get(index)?.as_range()
+}
+
+/// Wait until all keys under `prefix` created not later than `max_create_revision` are deleted.
+///
+/// Only the latest created key is watched each time, so that waiters are woken up one by one.
+pub(crate) async fn wait_deletes<S: GrpcService>(
+    kv: &mut KvClient<S>,
+    watch: &mut WatchClient<S>,
+    prefix: &[u8],
+    max_create_revision: i64,
+) -> Result<Option<pb::ResponseHeader>> {
+    loop {
+        let request = pb::RangeRequest {
+            max_create_revision,
+            ..first_create_request(prefix, pb::range_request::SortOrder::Descend)
+        };
+        let resp = kv.range(request).await?;
+
+        let Some(last) = resp.kvs.first() else {
+            return Ok(resp.header);
+        };
+
+        let revision = resp.header.map(|h| h.revision).unwrap_or_default();
+        wait_delete(watch, last.key.clone(), revision).await?;
+    }
+}
+
+/// Wait until `key` is deleted, watching from `revision`.
+async fn wait_delete<S: GrpcService>(
+    watch: &mut WatchClient<S>,
+    key: Vec<u8>,
+    revision: i64,
+) -> Result<()> {
+    let mut watcher = watch.do_watch(key).with_start_revision(revision).await?;
+
+    while let Some(resp) = watcher.message().await? {
+        let deleted = resp
+            .events
+            .iter()
+            .any(|ev| ev.r#type == pb::event::EventType::Delete as i32);
+        if deleted {
+            let _ = watcher.cancel().await;
+            return Ok(());
+        }
+        if resp.canceled {
+            return Err(Error::new(ErrKind::WatchCanceled, resp.cancel_reason));
+        }
+    }
+
+    Err(Error::new(
+        ErrKind::WatchFinished,
+        "lost watcher waiting for delete",
+    ))
+}
@@ -0,0 +1,221 @@
+use crate::client::Client;
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::GrpcService;
+use crate::kv::KvClient;
+use crate::pb;
+use crate::watch::WatchClient;
+
+use super::{Session, first_create_request, range_response, wait_deletes};
+
+/// A distributed mutex.
+///
+/// Every locker puts a key `{prefix}/{lease_id:x}` bound to its lease, the locker whose key has
+/// the lowest create revision holds the lock. Waiters only watch the deletion of the key created
+/// right before theirs.
+///
+/// ```no_run
+/// # use etcdv3client::{EtcdClient, Error, concurrency::Mutex};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let lease = client.grant_lease(10).await?;
+/// let mut mutex = Mutex::new(&client, "/my-lock", lease.id);
+///
+/// let guard = mutex.lock().await?;
+/// // do something exclusively
+/// guard.unlock().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mutex<S> {
+    kv: KvClient<S>,
+    watch: WatchClient<S>,
+    prefix: Vec<u8>,
+    lease_id: i64,
+}
+
+impl<S> Mutex<S>
+where
+    S: GrpcService,
+{
+    /// Create a mutex under `prefix`, the lock key is attached to `lease_id`.
+    pub fn new(client: &Client<S>, prefix: impl Into<Vec<u8>>, lease_id: i64) -> Self {
+        let mut prefix = prefix.into();
+        prefix.push(b'/');
+
+        Mutex {
+            kv: client.kv.clone(),
+            watch: client.watch.clone(),
+            prefix,
+            lease_id,
+        }
+    }
+
+    /// Create a mutex under `prefix`, the lock key is attached to the session lease.
+    pub fn with_session(session: &Session<S>, prefix: impl Into<Vec<u8>>) -> Self
+    where
+        S: 'static,
+    {
+        Self::new(session.client(), prefix, session.lease_id())
+    }
+
+    /// The lock key of this locker.
+    pub fn key(&self) -> Vec<u8> {
+        let mut key = self.prefix.clone();
+        key.extend_from_slice(format!("{:x}", self.lease_id).as_bytes());
+        key
+    }
+
+    /// Acquire the lock, wait until the lock is released by other lockers.
+    ///
+    /// The lock key is deleted if waiting fails, like when the session is expired.
+    /// If the returned future is dropped before completion, the lock key is kept until the
+    /// lease expires.
+    pub async fn lock(&mut self) -> Result<MutexGuard<S>> {
+        let (resp, revision) = self.try_acquire().await?;
+
+        if Self::is_acquired(&resp, revision) {
+            return Ok(self.guard(revision, resp.header));
+        }
+
+        match self.wait_owner(revision).await {
+            Ok(header) => Ok(self.guard(revision, header)),
+            Err(err) => {
+                // delete the lock key, otherwise it blocks other lockers until the lease expires.
+                let key = self.key();
+                let _ = self.kv.delete(key).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Try to acquire the lock, returns `ErrKind::Locked` error if it is held by another locker.
+    pub async fn try_lock(&mut self) -> Result<MutexGuard<S>> {
+        let (resp, revision) = self.try_acquire().await?;
+
+        if Self::is_acquired(&resp, revision) {
+            return Ok(self.guard(revision, resp.header));
+        }
+
+        // can not lock, so delete the key
+        let key = self.key();
+        self.kv.delete(key).await?;
+
+        Err(Error::from_kind(ErrKind::Locked))
+    }
+
+    /// Put the lock key if it does not exist, returns the create revision of the lock key.
+    async fn try_acquire(&mut self) -> Result<(pb::TxnResponse, i64)> {
+        let key = self.key();
+
+        let cmp = pb::Compare::new(
+            key.clone(),
+            pb::compare::CompareResult::Equal,
+            pb::compare::TargetUnion::CreateRevision(0),
+        );
+        let put = pb::PutRequest {
+            lease: self.lease_id,
+            ..pb::PutRequest::new(key.clone(), vec![])
+        };
+        let get = pb::RangeRequest::new(key);
+        let get_owner =
+            first_create_request(self.prefix.clone(), pb::range_request::SortOrder::Ascend);
+
+        let resp = self
+            .kv
+            .do_txn()
+            .with_if(vec![cmp])
+            .with_then(vec![put.into(), get_owner.clone().into()])
+            .with_else(vec![get.into(), get_owner.into()])
+            .await?;
+
+        let revision = if resp.succeeded {
+            resp.header.map(|h| h.revision).unwrap_or_default()
+        } else {
+            range_response(&resp, 0)
+                .and_then(|r| r.kvs.first())
+                .map(|kv| kv.create_revision)
+                .ok_or_else(|| Error::new(ErrKind::InvalidData, "lock key not found"))?
+        };
+
+        Ok((resp, revision))
+    }
+
+    /// Wait until the lock keys created before `revision` are deleted.
+    async fn wait_owner(&mut self, revision: i64) -> Result<Option<pb::ResponseHeader>> {
+        let header =
+            wait_deletes(&mut self.kv, &mut self.watch, &self.prefix, revision - 1).await?;
+
+        // make sure the lease is not expired, and the lock key still exists.
+        let key = self.key();
+        let resp = self.kv.do_range(key).await?;
+        if resp.kvs.is_empty() {
+            return Err(Error::from_kind(ErrKind::SessionExpired));
+        }
+
+        Ok(header)
+    }
+
+    fn is_acquired(resp: &pb::TxnResponse, revision: i64) -> bool {
+        match range_response(resp, 1).and_then(|r| r.kvs.first()) {
+            Some(owner) => owner.create_revision == revision,
+            None => true,
+        }
+    }
+
+    fn guard(&self, revision: i64, header: Option<pb::ResponseHeader>) -> MutexGuard<S> {
+        MutexGuard {
+            kv: self.kv.clone(),
+            key: self.key(),
+            revision,
+            header,
+        }
+    }
+}
+
+/// The holder of a locked [`Mutex`].
+///
+/// The lock is not released on drop, call [`MutexGuard::unlock`] or let the lease expire.
+#[derive(Debug)]
+#[must_use]
+pub struct MutexGuard<S> {
+    kv: KvClient<S>,
+    key: Vec<u8>,
+    revision: i64,
+    header: Option<pb::ResponseHeader>,
+}
+
+impl<S> MutexGuard<S>
+where
+    S: GrpcService,
+{
+    /// The lock key.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// The create revision of the lock key.
+    pub fn revision(&self) -> i64 {
+        self.revision
+    }
+
+    /// The response header when the lock was acquired.
+    pub fn header(&self) -> Option<&pb::ResponseHeader> {
+        self.header.as_ref()
+    }
+
+    /// A compare which only succeeds while the lock is held, use it to fence writes in a txn.
+    pub fn is_owner(&self) -> pb::Compare {
+        pb::Compare::new(
+            self.key.clone(),
+            pb::compare::CompareResult::Equal,
+            pb::compare::TargetUnion::CreateRevision(self.revision),
+        )
+    }
+
+    /// Release the lock.
+    pub async fn unlock(mut self) -> Result<()> {
+        self.kv.delete(self.key).await
+    }
+}
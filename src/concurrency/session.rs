@@ -0,0 +1,190 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, sleep_until, timeout_at};
+
+use crate::client::Client;
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::GrpcService;
+use crate::lease::{LeaseClient, LeaseKeepAliver};
+
+/// A lease which is kept alive in the background.
+///
+/// Keys attached to the session lease are deleted once the session is closed or the lease
+/// expires, which makes it the base for locks, elections and ephemeral keys.
+///
+/// ```no_run
+/// # use etcdv3client::{EtcdClient, Error, concurrency::Session};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let session = Session::new(&client, 10).await?;
+/// client.kv.do_put("/services/a", "addr").with_lease(session.lease_id()).await?;
+///
+/// tokio::select! {
+///     _ = session.done() => println!("session lost"),
+///     _ = tokio::signal::ctrl_c() => session.close().await?,
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Session<S> {
+    client: Client<S>,
+    lease_id: i64,
+    ttl: i64,
+    done: watch::Receiver<bool>,
+    handle: JoinHandle<()>,
+}
+
+impl<S> Session<S>
+where
+    S: GrpcService + 'static,
+{
+    /// Grant a lease with `ttl` seconds and keep it alive.
+    pub async fn new(client: &Client<S>, ttl: i64) -> Result<Self> {
+        let mut client = client.clone();
+
+        let resp = client.lease.grant(ttl).await?;
+
+        Self::start(client, resp.id, resp.ttl).await
+    }
+
+    /// Keep an existing lease alive.
+    pub async fn with_lease(client: &Client<S>, lease_id: i64) -> Result<Self> {
+        let mut client = client.clone();
+
+        let resp = client.lease.get_lease_info(lease_id, false).await?;
+        if resp.ttl <= 0 {
+            return Err(Error::from_kind(ErrKind::LeaseNotFound));
+        }
+
+        Self::start(client, lease_id, resp.granted_ttl).await
+    }
+
+    async fn start(mut client: Client<S>, lease_id: i64, ttl: i64) -> Result<Self> {
+        let aliver = client.lease.keep_alive(lease_id).await?;
+
+        let (done_tx, done) = watch::channel(false);
+        let lease = client.lease.clone();
+        let handle = tokio::spawn(async move {
+            keep_alive(lease, aliver, lease_id, ttl).await;
+            let _ = done_tx.send(true);
+        });
+
+        Ok(Session {
+            client,
+            lease_id,
+            ttl,
+            done,
+            handle,
+        })
+    }
+
+    /// The lease id of the session.
+    pub fn lease_id(&self) -> i64 {
+        self.lease_id
+    }
+
+    /// The granted ttl of the session lease, in seconds.
+    pub fn ttl(&self) -> i64 {
+        self.ttl
+    }
+
+    /// The client of the session.
+    pub fn client(&self) -> &Client<S> {
+        &self.client
+    }
+
+    /// Resolves when the lease can not be kept alive anymore.
+    pub fn done(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut done = self.done.clone();
+        async move {
+            let _ = done.wait_for(|done| *done).await;
+        }
+    }
+
+    /// Whether the lease can not be kept alive anymore.
+    pub fn is_done(&self) -> bool {
+        *self.done.borrow() || self.handle.is_finished()
+    }
+
+    /// Stop keeping the lease alive and revoke it.
+    pub async fn close(mut self) -> Result<()> {
+        self.handle.abort();
+        self.client.lease.revoke(self.lease_id).await
+    }
+}
+
+impl<S> Drop for Session<S> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Send keep alive requests every `ttl / 3`, until the lease is expired.
+async fn keep_alive<S: GrpcService>(
+    mut lease: LeaseClient<S>,
+    aliver: LeaseKeepAliver,
+    lease_id: i64,
+    ttl: i64,
+) {
+    let interval = Duration::from_secs(ttl.max(1) as u64) / 3;
+    let mut deadline = Instant::now() + Duration::from_secs(ttl.max(1) as u64);
+    let mut aliver = Some(aliver);
+
+    loop {
+        if let Some(ref mut alive) = aliver {
+            // the stream may hang without failing, so do not wait for it past the deadline.
+            let Ok(result) = timeout_at(deadline, alive.message()).await else {
+                tracing::debug!(lease_id, "lease expired");
+                return;
+            };
+            match result {
+                Ok(Some(resp)) if resp.ttl > 0 => {
+                    deadline = Instant::now() + Duration::from_secs(resp.ttl as u64);
+                }
+                Ok(Some(_)) => {
+                    tracing::debug!(lease_id, "lease expired");
+                    return;
+                }
+                Ok(None) => {
+                    aliver.take();
+                }
+                Err(err) => {
+                    tracing::warn!(lease_id, ?err, "keep alive failed");
+                    aliver.take();
+                }
+            }
+        }
+
+        sleep_until(deadline.min(Instant::now() + interval)).await;
+        if Instant::now() >= deadline {
+            tracing::debug!(lease_id, "lease expired");
+            return;
+        }
+
+        match aliver {
+            Some(ref mut alive) => {
+                if let Err(err) = alive.keep_alive().await {
+                    tracing::warn!(lease_id, ?err, "keep alive failed");
+                    aliver.take();
+                }
+            }
+            None => match timeout_at(deadline, lease.keep_alive(lease_id)).await {
+                Ok(Ok(alive)) => {
+                    aliver.replace(alive);
+                }
+                Ok(Err(err)) => {
+                    tracing::warn!(lease_id, ?err, "restart keep alive failed");
+                }
+                Err(_) => {
+                    tracing::debug!(lease_id, "lease expired");
+                    return;
+                }
+            },
+        }
+    }
+}
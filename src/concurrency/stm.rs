@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::client::Client;
+use crate::error::Result;
+use crate::grpc::GrpcService;
+use crate::kv::KvClient;
+use crate::pb;
+use crate::txn::{Cmp, Op, Txn};
+
+/// The isolation level of a [`Stm`] txn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Isolation {
+    /// All reads are at the revision of the first read, and the txn fails if any read or
+    /// written key was modified after that revision.
+    #[default]
+    SerializableSnapshot,
+    /// All reads are at the revision of the first read, and the txn fails if any read key was
+    /// modified since it was read.
+    Serializable,
+    /// Reads are at the latest revision, and the txn fails if any read key was modified since
+    /// it was read.
+    RepeatableReads,
+    /// Reads are at the latest revision, and the txn never fails.
+    ReadCommitted,
+}
+
+/// Software transactional memory, run a read-modify-write function atomically.
+///
+/// Reads of the function are recorded along with their revisions, and writes are buffered. The
+/// writes are committed in one txn with compares of the recorded revisions, and the function
+/// is retried once it conflicts with other writers, like `concurrency.NewSTM` of the Go client.
+///
+/// ```no_run
+/// # use etcdv3client::{EtcdClient, Error, concurrency::Stm};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let stm = Stm::new(&client);
+///
+/// // move 10 from account a to account b.
+/// stm.run(async |txn| {
+///     let parse = |v: Option<Vec<u8>>| {
+///         v.and_then(|v| String::from_utf8(v).ok())
+///             .and_then(|v| v.parse::<i64>().ok())
+///             .unwrap_or_default()
+///     };
+///     let a = parse(txn.get("/accounts/a").await?);
+///     let b = parse(txn.get("/accounts/b").await?);
+///     txn.put("/accounts/a", (a - 10).to_string());
+///     txn.put("/accounts/b", (b + 10).to_string());
+///     Ok(())
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stm<S> {
+    kv: KvClient<S>,
+    isolation: Isolation,
+}
+
+impl<S> Stm<S>
+where
+    S: GrpcService,
+{
+    pub fn new(client: &Client<S>) -> Self {
+        Stm {
+            kv: client.kv.clone(),
+            isolation: Isolation::default(),
+        }
+    }
+
+    pub fn with_isolation(mut self, isolation: Isolation) -> Self {
+        self.isolation = isolation;
+        self
+    }
+
+    /// Run `apply` until its writes are committed without conflicts.
+    ///
+    /// Nothing is written if `apply` returns error, and the error is returned.
+    pub async fn run<T>(
+        &self,
+        mut apply: impl AsyncFnMut(&mut StmTxn<S>) -> Result<T>,
+    ) -> Result<T> {
+        let mut prefetch = HashMap::new();
+
+        loop {
+            let mut txn = StmTxn {
+                kv: self.kv.clone(),
+                isolation: self.isolation,
+                rset: HashMap::new(),
+                wset: BTreeMap::new(),
+                prefetch,
+                read_revision: None,
+            };
+
+            let value = apply(&mut txn).await?;
+
+            match txn.commit().await? {
+                None => return Ok(value),
+                Some(fetched) => {
+                    tracing::debug!(isolation = ?self.isolation, "stm txn conflicted, retry");
+                    prefetch = fetched;
+                }
+            }
+        }
+    }
+}
+
+/// The txn handle passed to the function run by [`Stm`].
+#[derive(Debug)]
+pub struct StmTxn<S> {
+    kv: KvClient<S>,
+    isolation: Isolation,
+    rset: HashMap<Vec<u8>, pb::RangeResponse>,
+    /// `None` means the key is deleted.
+    wset: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    /// Keys fetched by the conflicted commit, to save a round trip on retry.
+    prefetch: HashMap<Vec<u8>, pb::RangeResponse>,
+    read_revision: Option<i64>,
+}
+
+impl<S> StmTxn<S>
+where
+    S: GrpcService,
+{
+    /// Get the value of the key, the writes of this txn are visible.
+    pub async fn get(&mut self, key: impl Into<Vec<u8>>) -> Result<Option<Vec<u8>>> {
+        let key = key.into();
+        if let Some(value) = self.wset.get(&key) {
+            return Ok(value.clone());
+        }
+
+        let resp = self.fetch(key).await?;
+        Ok(resp.kvs.first().map(|kv| kv.value.clone()))
+    }
+
+    /// Get the mod revision of the key, `0` means the key does not exist.
+    pub async fn rev(&mut self, key: impl Into<Vec<u8>>) -> Result<i64> {
+        let resp = self.fetch(key.into()).await?;
+        Ok(resp
+            .kvs
+            .first()
+            .map(|kv| kv.mod_revision)
+            .unwrap_or_default())
+    }
+
+    /// Put the key-value pair once the txn is committed.
+    pub fn put(&mut self, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) {
+        self.wset.insert(key.into(), Some(value.into()));
+    }
+
+    /// Delete the key once the txn is committed.
+    pub fn del(&mut self, key: impl Into<Vec<u8>>) {
+        self.wset.insert(key.into(), None);
+    }
+
+    fn is_serializable(&self) -> bool {
+        matches!(
+            self.isolation,
+            Isolation::SerializableSnapshot | Isolation::Serializable
+        )
+    }
+
+    async fn fetch(&mut self, key: Vec<u8>) -> Result<&pb::RangeResponse> {
+        if !self.rset.contains_key(&key) {
+            let resp = match self.prefetch.remove(&key) {
+                Some(resp) => resp,
+                None => {
+                    let mut request = pb::RangeRequest::new(key.clone());
+                    if let Some(revision) = self.read_revision {
+                        request.revision = revision;
+                        request.serializable = true;
+                    }
+                    self.kv.range(request).await?
+                }
+            };
+
+            // the base revision of the txn is defined by the first read.
+            if self.is_serializable() && self.read_revision.is_none() {
+                self.read_revision = resp.header.as_ref().map(|h| h.revision);
+            }
+            self.rset.insert(key.clone(), resp);
+        }
+
+        Ok(&self.rset[&key])
+    }
+
+    /// Commit the writes, returns the latest read keys if the txn conflicted.
+    async fn commit(self) -> Result<Option<HashMap<Vec<u8>, pb::RangeResponse>>> {
+        let compares = commit_compares(self.isolation, &self.rset, &self.wset);
+
+        // fetch the read keys on conflict, to save a round trip on retry.
+        let keys: Vec<Vec<u8>> = if self.is_serializable() {
+            self.rset.into_keys().collect()
+        } else {
+            Vec::new()
+        };
+        let gets = keys.iter().map(|key| Op::get(key.clone()));
+        let puts = self.wset.into_iter().map(|(key, value)| match value {
+            Some(value) => Op::put(key, value),
+            None => Op::delete(key),
+        });
+
+        let txn = Txn::new().when(compares).and_then(puts).or_else(gets);
+        let mut kv = self.kv;
+        let result = kv.commit(txn).await?;
+        if result.succeeded() {
+            return Ok(None);
+        }
+
+        let fetched = keys
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, key)| Some((key, result.range(i)?.clone())))
+            .collect();
+        Ok(Some(fetched))
+    }
+}
+
+/// The compares of the commit, which fail once the txn conflicts with other writers.
+fn commit_compares(
+    isolation: Isolation,
+    rset: &HashMap<Vec<u8>, pb::RangeResponse>,
+    wset: &BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+) -> Vec<Cmp> {
+    let mut compares = Vec::new();
+    if isolation != Isolation::ReadCommitted {
+        for (key, resp) in rset.iter() {
+            let revision = resp
+                .kvs
+                .first()
+                .map(|kv| kv.mod_revision)
+                .unwrap_or_default();
+            compares.push(Cmp::mod_revision(key.clone()).eq(revision));
+        }
+    }
+    if isolation == Isolation::SerializableSnapshot {
+        let first = rset
+            .values()
+            .filter_map(|resp| resp.header.as_ref().map(|h| h.revision))
+            .min()
+            .unwrap_or(i64::MAX - 1);
+        for key in wset.keys() {
+            compares.push(Cmp::mod_revision(key.clone()).lt(first + 1));
+        }
+    }
+    compares
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::{Isolation, commit_compares};
+    use crate::pb;
+    use crate::txn::Cmp;
+
+    #[test]
+    fn test_commit_compares() {
+        // "a" was read at revision 10, when it was modified at revision 7.
+        let read = pb::RangeResponse {
+            header: Some(pb::ResponseHeader {
+                revision: 10,
+                ..Default::default()
+            }),
+            kvs: vec![pb::KeyValue {
+                key: b"a".to_vec(),
+                mod_revision: 7,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let rset = HashMap::from([(b"a".to_vec(), read)]);
+        let wset = BTreeMap::from([(b"a".to_vec(), Some(b"1".to_vec())), (b"b".to_vec(), None)]);
+
+        assert_eq!(
+            commit_compares(Isolation::SerializableSnapshot, &rset, &wset),
+            vec![
+                Cmp::mod_revision("a").eq(7),
+                Cmp::mod_revision("a").lt(11),
+                Cmp::mod_revision("b").lt(11),
+            ]
+        );
+        assert_eq!(
+            commit_compares(Isolation::Serializable, &rset, &wset),
+            vec![Cmp::mod_revision("a").eq(7)]
+        );
+        assert_eq!(
+            commit_compares(Isolation::RepeatableReads, &rset, &wset),
+            vec![Cmp::mod_revision("a").eq(7)]
+        );
+        assert!(commit_compares(Isolation::ReadCommitted, &rset, &wset).is_empty());
+
+        // a missing key is compared with revision 0.
+        let rset = HashMap::from([(b"c".to_vec(), pb::RangeResponse::default())]);
+        assert_eq!(
+            commit_compares(Isolation::RepeatableReads, &rset, &BTreeMap::new()),
+            vec![Cmp::mod_revision("c").eq(0)]
+        );
+    }
+}
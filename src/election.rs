@@ -0,0 +1,450 @@
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::GrpcService;
+use crate::pb;
+use crate::utils::{new_request, with_deadline};
+
+use std::time::Duration;
+use tonic::IntoRequest;
+
+#[derive(Debug, Clone)]
+pub struct InnerElectionClient<S> {
+    service: S,
+}
+impl<S> InnerElectionClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+    pub async fn campaign(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::CampaignRequest>,
+    ) -> Result<tonic::Response<pb::CampaignResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/v3electionpb.Election/Campaign");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn proclaim(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::ProclaimRequest>,
+    ) -> Result<tonic::Response<pb::ProclaimResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/v3electionpb.Election/Proclaim");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn leader(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::LeaderRequest>,
+    ) -> Result<tonic::Response<pb::LeaderResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/v3electionpb.Election/Leader");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn observe(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::LeaderRequest>,
+    ) -> Result<tonic::Response<tonic::codec::Streaming<pb::LeaderResponse>>> {
+        let path = http::uri::PathAndQuery::from_static("/v3electionpb.Election/Observe");
+        self.service
+            .server_streaming(request.into_request(), path)
+            .await
+    }
+    pub async fn resign(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::ResignRequest>,
+    ) -> Result<tonic::Response<pb::ResignResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/v3electionpb.Election/Resign");
+        self.service.unary(request.into_request(), path).await
+    }
+}
+#[derive(Debug, Clone)]
+pub struct ElectionClient<S> {
+    inner: InnerElectionClient<S>,
+}
+impl<S> ElectionClient<S>
+where
+    S: GrpcService,
+{
+    pub async fn campaign(&mut self, request: pb::CampaignRequest) -> Result<pb::CampaignResponse> {
+        self.inner
+            .campaign(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn proclaim(&mut self, request: pb::ProclaimRequest) -> Result<pb::ProclaimResponse> {
+        self.inner
+            .proclaim(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn leader(&mut self, request: pb::LeaderRequest) -> Result<pb::LeaderResponse> {
+        self.inner
+            .leader(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn observe(
+        &mut self,
+        request: pb::LeaderRequest,
+    ) -> Result<tonic::codec::Streaming<pb::LeaderResponse>> {
+        self.inner
+            .observe(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn resign(&mut self, request: pb::ResignRequest) -> Result<pb::ResignResponse> {
+        self.inner
+            .resign(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+}
+
+impl<S> ElectionClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        ElectionClient {
+            inner: InnerElectionClient::new(service),
+        }
+    }
+
+    /// Do campaign request, served by the v3election service of etcd server.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error, ElectionClient};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let lease = client.grant_lease(10).await?;
+    /// let mut election = ElectionClient::new(client.service());
+    /// let leader = election.elect("my-election", lease.id, "node-1").await?;
+    /// // do something as the leader
+    /// election.resign_leader(leader).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn do_campaign(
+        &mut self,
+        name: impl Into<Vec<u8>>,
+        lease_id: i64,
+        value: impl Into<Vec<u8>>,
+    ) -> DoCampaignRequest<'_, S> {
+        pb::CampaignRequest::new(name, lease_id, value).build(self)
+    }
+
+    /// Campaign in the election `name` and wait until elected, returns the leadership key.
+    pub async fn elect(
+        &mut self,
+        name: impl Into<Vec<u8>>,
+        lease_id: i64,
+        value: impl Into<Vec<u8>>,
+    ) -> Result<pb::LeaderKey> {
+        let resp = self.do_campaign(name, lease_id, value).await?;
+        resp.leader
+            .ok_or_else(|| Error::new(ErrKind::InvalidData, "leader key not found"))
+    }
+
+    pub fn do_proclaim(
+        &mut self,
+        leader: pb::LeaderKey,
+        value: impl Into<Vec<u8>>,
+    ) -> DoProclaimRequest<'_, S> {
+        pb::ProclaimRequest::new(leader, value).build(self)
+    }
+
+    /// Update the leader value with the leadership key.
+    pub async fn proclaim_leader(
+        &mut self,
+        leader: pb::LeaderKey,
+        value: impl Into<Vec<u8>>,
+    ) -> Result<()> {
+        self.do_proclaim(leader, value).await?;
+        Ok(())
+    }
+
+    pub fn do_leader(&mut self, name: impl Into<Vec<u8>>) -> DoLeaderRequest<'_, S> {
+        pb::LeaderRequest::new(name).build(self)
+    }
+
+    /// Get the current leader key value of the election `name`.
+    pub async fn get_leader(&mut self, name: impl Into<Vec<u8>>) -> Result<pb::KeyValue> {
+        let resp = self.do_leader(name).await?;
+        resp.kv
+            .ok_or_else(|| Error::from_kind(ErrKind::ElectionNoLeader))
+    }
+
+    /// Observe the leader changes of the election `name`.
+    pub async fn observe_leader(
+        &mut self,
+        name: impl Into<Vec<u8>>,
+    ) -> Result<tonic::codec::Streaming<pb::LeaderResponse>> {
+        self.observe(pb::LeaderRequest::new(name)).await
+    }
+
+    pub fn do_resign(&mut self, leader: pb::LeaderKey) -> DoResignRequest<'_, S> {
+        pb::ResignRequest::new(leader).build(self)
+    }
+
+    /// Give up the leadership.
+    pub async fn resign_leader(&mut self, leader: pb::LeaderKey) -> Result<()> {
+        self.do_resign(leader).await?;
+        Ok(())
+    }
+}
+
+impl pb::CampaignRequest {
+    pub fn new(name: impl Into<Vec<u8>>, lease: i64, value: impl Into<Vec<u8>>) -> Self {
+        pb::CampaignRequest {
+            name: name.into(),
+            lease,
+            value: value.into(),
+        }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut ElectionClient<S>) -> DoCampaignRequest<'_, S> {
+        DoCampaignRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoCampaignRequest<'a, S> {
+    pub request: pb::CampaignRequest,
+    pub(crate) client: &'a mut ElectionClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoCampaignRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ElectionClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_name(mut self, name: Vec<u8>) -> Self {
+        self.request.name = name;
+        self
+    }
+    pub fn with_lease(mut self, lease: i64) -> Self {
+        self.request.lease = lease;
+        self
+    }
+    pub fn with_value(mut self, value: Vec<u8>) -> Self {
+        self.request.value = value;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoCampaignRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::CampaignResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::CampaignResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoCampaignRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.campaign(request)).await
+        })
+    }
+}
+
+impl pb::ProclaimRequest {
+    pub fn new(leader: pb::LeaderKey, value: impl Into<Vec<u8>>) -> Self {
+        pb::ProclaimRequest {
+            leader: Some(leader),
+            value: value.into(),
+        }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut ElectionClient<S>) -> DoProclaimRequest<'_, S> {
+        DoProclaimRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoProclaimRequest<'a, S> {
+    pub request: pb::ProclaimRequest,
+    pub(crate) client: &'a mut ElectionClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoProclaimRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ElectionClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_leader(mut self, leader: pb::LeaderKey) -> Self {
+        self.request.leader = Some(leader);
+        self
+    }
+    pub fn with_value(mut self, value: Vec<u8>) -> Self {
+        self.request.value = value;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoProclaimRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::ProclaimResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::ProclaimResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoProclaimRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.proclaim(request)).await
+        })
+    }
+}
+
+impl pb::LeaderRequest {
+    pub fn new(name: impl Into<Vec<u8>>) -> Self {
+        pb::LeaderRequest { name: name.into() }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut ElectionClient<S>) -> DoLeaderRequest<'_, S> {
+        DoLeaderRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoLeaderRequest<'a, S> {
+    pub request: pb::LeaderRequest,
+    pub(crate) client: &'a mut ElectionClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoLeaderRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ElectionClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_name(mut self, name: Vec<u8>) -> Self {
+        self.request.name = name;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoLeaderRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::LeaderResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::LeaderResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoLeaderRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.leader(request)).await
+        })
+    }
+}
+
+impl pb::ResignRequest {
+    pub fn new(leader: pb::LeaderKey) -> Self {
+        pb::ResignRequest {
+            leader: Some(leader),
+        }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut ElectionClient<S>) -> DoResignRequest<'_, S> {
+        DoResignRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoResignRequest<'a, S> {
+    pub request: pb::ResignRequest,
+    pub(crate) client: &'a mut ElectionClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoResignRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut ElectionClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_leader(mut self, leader: pb::LeaderKey) -> Self {
+        self.request.leader = Some(leader);
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoResignRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::ResignResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::ResignResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoResignRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.resign(request)).await
+        })
+    }
+}
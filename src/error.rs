@@ -68,6 +68,8 @@ pub enum ErrKind {
     Grpc,
     Endpoint,
     ConnectFailed,
+    /// The request did not complete before its deadline.
+    DeadlineExceeded,
     InvalidData,
     // lease errors
     LeaseRequestFailed,
@@ -76,6 +78,17 @@ pub enum ErrKind {
     WatchStartFailed,
     WatchCanceled,
     WatchFinished,
+    // maintenance errors
+    SnapshotFailed,
+    SnapshotCorrupted,
+    // concurrency errors
+    Locked,
+    SessionExpired,
+    ElectionNotLeader,
+    ElectionNoLeader,
+    // retry errors
+    /// The request failed in a way that it may or may not have been applied.
+    UnknownOutcome,
 }
 
 #[derive(Debug)]
@@ -118,6 +131,102 @@ impl Error {
     pub fn should_refresh_token(&self) -> bool {
         self.kind == ErrKind::InvalidAuthToken || self.kind == ErrKind::AuthOldRevision
     }
+
+    /// Whether the server is unavailable or the stream is broken, so that retrying may succeed.
+    pub fn is_unavailable(&self) -> bool {
+        match self.kind {
+            ErrKind::ConnectFailed
+            | ErrKind::WatchStartFailed
+            | ErrKind::WatchRequestFailed
+            | ErrKind::WatchFinished => true,
+            _ => match self.status() {
+                Some(status) => status.code() == Code::Unavailable,
+                None => false,
+            },
+        }
+    }
+
+    /// Whether the error is transient, so that retrying may succeed.
+    pub(crate) fn is_transient(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrKind::NoLeader
+                | ErrKind::LeaderChanged
+                | ErrKind::Stopped
+                | ErrKind::Timeout
+                | ErrKind::TimeoutDueToLeaderFail
+                | ErrKind::TimeoutDueToConnectionLost
+                | ErrKind::TimeoutWaitAppliedIndex
+                | ErrKind::Unhealthy
+                | ErrKind::TooManyRequests
+        ) || self.is_unavailable()
+            || self.is_not_sent()
+    }
+
+    /// Whether the request is known to be not sent, or rejected by the server before applying.
+    pub(crate) fn is_not_sent(&self) -> bool {
+        match self.kind {
+            ErrKind::NoLeader | ErrKind::TooManyRequests | ErrKind::ConnectFailed => true,
+            ErrKind::Grpc if self.cause.is::<NotReady>() => true,
+            _ => match self.status() {
+                Some(status) if status.code() == Code::Unavailable => {
+                    let mut source = std::error::Error::source(status);
+                    while let Some(err) = source {
+                        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+                            return err.kind() == std::io::ErrorKind::ConnectionRefused;
+                        }
+                        source = err.source();
+                    }
+                    false
+                }
+                _ => false,
+            },
+        }
+    }
+
+    /// The gRPC status of the error, if it was returned by the server or the channel.
+    pub(crate) fn status(&self) -> Option<&Status> {
+        self.cause.downcast_ref::<Status>()
+    }
+
+    /// The compact revision of an `ErrKind::Compacted` error raised by a watcher.
+    pub fn compact_revision(&self) -> Option<i64> {
+        self.cause
+            .downcast_ref::<CompactRevision>()
+            .map(|CompactRevision(revision)| *revision)
+    }
+}
+
+/// The cause of `ErrKind::Compacted` error when the watched revision has been compacted.
+#[derive(Debug)]
+pub(crate) struct CompactRevision(pub(crate) i64);
+
+impl Display for CompactRevision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "required revision has been compacted, compact revision {}",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for CompactRevision {}
+
+/// The cause of `ErrKind::Grpc` error when the service was not ready, so the request is not sent.
+#[derive(Debug)]
+pub(crate) struct NotReady(pub(crate) Box<dyn std::error::Error + Send + Sync>);
+
+impl Display for NotReady {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Service was not ready: {}", self.0)
+    }
+}
+
+impl std::error::Error for NotReady {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
 }
 
 impl Display for Error {
@@ -259,6 +368,12 @@ impl From<Status> for Error {
             (Code::FailedPrecondition, "etcdserver: no inflight downgrade job") => {
                 ErrKind::NoInflightDowngrade
             }
+            (Code::Unknown, "election: not leader") => ErrKind::ElectionNotLeader,
+            (Code::Unknown, "election: no leader") => ErrKind::ElectionNoLeader,
+            (Code::Unknown, "mutex: session is expired") => ErrKind::SessionExpired,
+            (Code::DeadlineExceeded, _) => ErrKind::DeadlineExceeded,
+            // the local deadline of tonic channel.
+            (Code::Cancelled, "Timeout expired") => ErrKind::DeadlineExceeded,
             _ => ErrKind::Grpc,
         };
 
@@ -1,8 +1,22 @@
 use http::uri::PathAndQuery;
+use prost::bytes::Bytes;
 use std::future::Future;
+use std::sync::Arc;
+use tonic::body::Body;
 use tonic::metadata::AsciiMetadataValue;
+use tower::util::BoxCloneSyncService;
+use tower::{BoxError, Service, ServiceExt};
 
-use crate::{auth::InnerAuthClient, error::Result, utils::TOKEN_FIELD_NAME};
+use crate::{
+    auth::InnerAuthClient,
+    client::{ClientOptions, EndpointSync},
+    error::{NotReady, Result},
+    utils::{GRPC_TIMEOUT_FIELD_NAME, TOKEN_FIELD_NAME},
+};
+
+/// The methods blocking until the lock or the leadership is acquired, the default timeout does
+/// not apply to them.
+const BLOCKING_METHODS: &[&str] = &["/v3lockpb.Lock/Lock", "/v3electionpb.Election/Campaign"];
 
 pub trait GrpcService: Send + Clone + std::fmt::Debug {
     fn unary<M, T>(
@@ -44,33 +58,193 @@ pub trait GrpcService: Send + Clone + std::fmt::Debug {
         T: prost::Message + Default + Send + Sync + 'static;
 }
 
+/// Observe a response stream, the observer lives as long as the stream is held.
+pub(crate) trait StreamObserver: Send + Sync {
+    /// Called with the number of events of each received watch response.
+    fn watch_events(&self, _count: usize) {}
+}
+
+/// The observer of a response stream, passed to the holder of the stream in the extensions of
+/// the response, like the gauge of active streams of [`Metrics`](crate::Metrics).
+#[derive(Clone, Default)]
+pub(crate) struct StreamGuard(Option<Arc<dyn StreamObserver>>);
+
+impl StreamGuard {
+    /// Attach the observer to the streaming response.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn attach<T>(
+        resp: &mut tonic::Response<T>,
+        observer: impl StreamObserver + 'static,
+    ) {
+        resp.extensions_mut()
+            .insert(StreamGuard(Some(Arc::new(observer))));
+    }
+
+    /// Take the observer out of the streaming response, or an empty guard without one.
+    pub(crate) fn take<T>(resp: &mut tonic::Response<T>) -> Self {
+        resp.extensions_mut()
+            .remove::<StreamGuard>()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn watch_events(&self, count: usize) {
+        if let Some(observer) = &self.0 {
+            observer.watch_events(count);
+        }
+    }
+}
+
+/// The http service under [`TonicClient`], a channel wrapped by the layers of
+/// [`ClientBuilder::layer`](crate::ClientBuilder::layer).
+pub type HttpService = BoxCloneSyncService<http::Request<Body>, http::Response<Body>, HttpError>;
+
+/// The error of [`HttpService`], the error of the underlying service is its source.
+#[derive(Debug)]
+pub struct HttpError(BoxError);
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// Box the http service, the response body is converted to the tonic body.
+pub(crate) fn box_service<S, B>(service: S) -> HttpService
+where
+    S: Service<http::Request<Body>, Response = http::Response<B>> + Clone + Send + Sync + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    BoxCloneSyncService::new(
+        service
+            .map_response(|resp: http::Response<B>| resp.map(Body::new))
+            .map_err(|err| HttpError(err.into())),
+    )
+}
+
 #[derive(Debug, Clone)]
 pub struct TonicClient {
-    inner: tonic::client::Grpc<tonic::transport::Channel>,
+    inner: tonic::client::Grpc<HttpService>,
+    options: Arc<ClientOptions>,
+    endpoint_sync: Option<Arc<EndpointSync>>,
 }
 
 impl TonicClient {
     pub fn new(channel: tonic::transport::Channel) -> Self {
+        Self::with_options(channel, Arc::default())
+    }
+
+    /// Create the client over any http service, like a channel wrapped by tower middlewares.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{Client, Error, grpc::TonicClient};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// let channel = tonic::transport::Channel::from_static("http://localhost:2379").connect_lazy();
+    /// let service = tower::ServiceBuilder::new()
+    ///     .concurrency_limit(64)
+    ///     .service(channel);
+    /// let mut client = Client::with_service(TonicClient::with_service(service));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_service<S, B>(service: S) -> Self
+    where
+        S: Service<http::Request<Body>, Response = http::Response<B>>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<BoxError>,
+        B: http_body::Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        Self::from_service(box_service(service), Arc::default())
+    }
+
+    pub(crate) fn with_options(
+        channel: tonic::transport::Channel,
+        options: Arc<ClientOptions>,
+    ) -> Self {
+        let service = options.layer(channel);
+        Self::from_service(service, options)
+    }
+
+    fn from_service(service: HttpService, options: Arc<ClientOptions>) -> Self {
+        let mut inner = tonic::client::Grpc::new(service);
+        if let Some(limit) = options.max_decoding_message_size {
+            inner = inner.max_decoding_message_size(limit);
+        }
+        if let Some(limit) = options.max_encoding_message_size {
+            inner = inner.max_encoding_message_size(limit);
+        }
+
         Self {
-            inner: tonic::client::Grpc::new(channel),
+            inner,
+            options,
+            endpoint_sync: None,
         }
     }
 
+    pub(crate) fn with_endpoint_sync(mut self, endpoint_sync: Option<Arc<EndpointSync>>) -> Self {
+        self.endpoint_sync = endpoint_sync;
+        self
+    }
+
+    pub(crate) fn endpoint_sync(&self) -> Option<Arc<EndpointSync>> {
+        self.endpoint_sync.clone()
+    }
+
+    /// Limit the max size of a decoded message, 4MiB by default.
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.inner = self.inner.max_decoding_message_size(limit);
+        self
+    }
+
+    /// Limit the max size of an encoded message, unlimited by default.
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.inner = self.inner.max_encoding_message_size(limit);
+        self
+    }
+
+    /// Connect to another endpoint with the same options.
+    pub(crate) async fn connect_to(&self, endpoint: http::Uri) -> Result<Self> {
+        let channel = self.options.connect_to(endpoint).await?;
+
+        Ok(Self::with_options(channel, self.options.clone()))
+    }
+
     pub async fn unary<M, T>(
         &mut self,
-        req: tonic::Request<M>,
+        mut req: tonic::Request<M>,
         path: PathAndQuery,
     ) -> Result<tonic::Response<T>>
     where
         M: prost::Message + Clone + Send + Sync + 'static,
         T: prost::Message + Default + Send + Sync + 'static,
     {
-        self.inner.ready().await.map_err(|e| {
-            crate::Error::new(
-                crate::ErrKind::Grpc,
-                format!("Service was not ready: {}", e),
-            )
-        })?;
+        // the default timeout, the channel enforces `grpc-timeout` locally as well.
+        let timeout = self.options.timeout.filter(|_| {
+            !BLOCKING_METHODS.contains(&path.path())
+                && !req.metadata().contains_key(GRPC_TIMEOUT_FIELD_NAME)
+        });
+        if let Some(timeout) = timeout {
+            req.set_timeout(timeout);
+        }
+
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| crate::Error::new(crate::ErrKind::Grpc, NotReady(e.into())))?;
         let codec = tonic::codec::ProstCodec::default();
 
         self.inner.unary(req, path, codec).await.map_err(Into::into)
@@ -86,12 +260,10 @@ impl TonicClient {
         M: prost::Message + Send + Sync + 'static,
         T: prost::Message + Default + Send + Sync + 'static,
     {
-        self.inner.ready().await.map_err(|e| {
-            crate::Error::new(
-                crate::ErrKind::Grpc,
-                format!("Service was not ready: {}", e),
-            )
-        })?;
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| crate::Error::new(crate::ErrKind::Grpc, NotReady(e.into())))?;
         let codec = tonic::codec::ProstCodec::default();
 
         self.inner
@@ -109,12 +281,10 @@ impl TonicClient {
         M: prost::Message + Clone + Send + Sync + 'static,
         T: prost::Message + Default + Send + Sync + 'static,
     {
-        self.inner.ready().await.map_err(|e| {
-            crate::Error::new(
-                crate::ErrKind::Grpc,
-                format!("Service was not ready: {}", e),
-            )
-        })?;
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| crate::Error::new(crate::ErrKind::Grpc, NotReady(e.into())))?;
         let codec = tonic::codec::ProstCodec::default();
 
         self.inner
@@ -133,12 +303,10 @@ impl TonicClient {
         M: prost::Message + Send + Sync + 'static,
         T: prost::Message + Default + Send + Sync + 'static,
     {
-        self.inner.ready().await.map_err(|e| {
-            crate::Error::new(
-                crate::ErrKind::Grpc,
-                format!("Service was not ready: {}", e),
-            )
-        })?;
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| crate::Error::new(crate::ErrKind::Grpc, NotReady(e.into())))?;
         let codec = tonic::codec::ProstCodec::default();
 
         self.inner
@@ -317,6 +485,19 @@ where
         self.inner.streaming(req, path).await
     }
 
+    pub(crate) fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Create a new interceptor over `inner` with the same credential and token.
+    pub(crate) fn with_inner<D>(&self, inner: D) -> CredentialInterceptor<D> {
+        CredentialInterceptor {
+            credential: self.credential.clone(),
+            token: self.token.clone(),
+            inner,
+        }
+    }
+
     /// Clone request.
     fn clone_request<M: Clone>(req: tonic::Request<M>) -> (tonic::Request<M>, tonic::Request<M>) {
         let (metadata, extensions, message) = req.into_parts();
@@ -425,3 +606,152 @@ where
         CredentialInterceptor::streaming(self, req, path).await
     }
 }
+
+/// A service answering unary requests with scripted responses, recording the requests.
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StubService {
+    requests: Arc<std::sync::Mutex<Vec<Bytes>>>,
+    responses: Arc<std::sync::Mutex<std::collections::VecDeque<Bytes>>>,
+}
+
+#[cfg(test)]
+impl StubService {
+    /// Answer the next request with `resp`.
+    pub(crate) fn respond<T: prost::Message>(&self, resp: T) {
+        let resp = resp.encode_to_vec().into();
+        self.responses.lock().unwrap().push_back(resp);
+    }
+
+    /// The requests received so far.
+    pub(crate) fn requests<M: prost::Message + Default>(&self) -> Vec<M> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|req| M::decode(req.clone()).unwrap())
+            .collect()
+    }
+
+    /// The request received at `index`, for requests of different types.
+    pub(crate) fn request<M: prost::Message + Default>(&self, index: usize) -> M {
+        M::decode(self.requests.lock().unwrap()[index].clone()).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl GrpcService for StubService {
+    async fn unary<M, T>(
+        &mut self,
+        req: tonic::Request<M>,
+        _path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let req = req.into_inner().encode_to_vec().into();
+        self.requests.lock().unwrap().push(req);
+
+        let resp = self.responses.lock().unwrap().pop_front();
+        let resp = resp.ok_or_else(|| crate::Error::new(crate::ErrKind::Grpc, "no response"))?;
+        let resp = T::decode(resp).map_err(|err| crate::Error::new(crate::ErrKind::Grpc, err))?;
+
+        Ok(tonic::Response::new(resp))
+    }
+
+    async fn client_streaming<S, M, T>(
+        &mut self,
+        _req: tonic::Request<S>,
+        _path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        unimplemented!("streams are not scripted")
+    }
+
+    async fn server_streaming<M, T>(
+        &mut self,
+        _req: tonic::Request<M>,
+        _path: PathAndQuery,
+    ) -> Result<tonic::Response<tonic::Streaming<T>>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        unimplemented!("streams are not scripted")
+    }
+
+    async fn streaming<S, M, T>(
+        &mut self,
+        _req: tonic::Request<S>,
+        _path: PathAndQuery,
+    ) -> Result<tonic::Response<tonic::Streaming<T>>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        unimplemented!("streams are not scripted")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use http::uri::PathAndQuery;
+    use tonic::body::Body;
+
+    use super::{TonicClient, box_service};
+    use crate::client::ClientOptions;
+    use crate::utils::GRPC_TIMEOUT_FIELD_NAME;
+    use crate::{ErrKind, pb};
+
+    #[tokio::test]
+    async fn test_with_service() {
+        let service = tower::service_fn(async |_req: http::Request<Body>| {
+            Err::<http::Response<Body>, _>(tonic::TimeoutExpired(()))
+        });
+
+        let mut client = TonicClient::with_service(service);
+        let path = PathAndQuery::from_static("/etcdserverpb.KV/Range");
+        let err = client
+            .unary::<_, pb::RangeResponse>(tonic::Request::new(pb::RangeRequest::new("a")), path)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrKind::DeadlineExceeded);
+    }
+
+    #[tokio::test]
+    async fn test_default_timeout() {
+        let timeouts = Arc::new(Mutex::new(Vec::new()));
+        let service = {
+            let timeouts = timeouts.clone();
+            tower::service_fn(move |req: http::Request<Body>| {
+                let timeout = req.headers().contains_key(GRPC_TIMEOUT_FIELD_NAME);
+                timeouts.lock().unwrap().push(timeout);
+                async { Err::<http::Response<Body>, _>(tonic::TimeoutExpired(())) }
+            })
+        };
+        let options = ClientOptions {
+            timeout: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let mut client = TonicClient::from_service(box_service(service), Arc::new(options));
+
+        let path = PathAndQuery::from_static("/etcdserverpb.KV/Range");
+        let req = tonic::Request::new(pb::RangeRequest::new("a"));
+        let _ = client.unary::<_, pb::RangeResponse>(req, path).await;
+
+        let path = PathAndQuery::from_static("/v3lockpb.Lock/Lock");
+        let req = tonic::Request::new(pb::LockRequest::default());
+        let _ = client.unary::<_, pb::LockResponse>(req, path).await;
+
+        assert_eq!(*timeouts.lock().unwrap(), vec![true, false]);
+    }
+}
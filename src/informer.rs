@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+
+use futures::{Stream, StreamExt};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::task::JoinHandle;
+
+use crate::client::Client;
+use crate::error::{ErrKind, Result};
+use crate::grpc::GrpcService;
+use crate::kv::{DEFAULT_PAGE_SIZE, KvClient, range_pages};
+use crate::pb;
+use crate::utils::Backoff;
+use crate::watch::WatchClient;
+
+type Cache = Arc<RwLock<BTreeMap<Vec<u8>, pb::KeyValue>>>;
+
+/// A change of the keys cached by [`Informer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InformerEvent {
+    Added(pb::KeyValue),
+    Updated {
+        old: pb::KeyValue,
+        new: pb::KeyValue,
+    },
+    Deleted(pb::KeyValue),
+}
+
+/// An in-memory mirror of the keys under a prefix.
+///
+/// The keys are listed page by page at one revision, then watched from the next revision, so
+/// that no change is lost between listing and watching. If the watched revision was compacted,
+/// the keys are listed again, and the differences are yielded as changes.
+///
+/// The informer is a `Stream` of changes, the changes are buffered until they are consumed.
+///
+/// ```no_run
+/// # use etcdv3client::{EtcdClient, Error, Informer};
+/// # use futures::StreamExt;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let mut informer = Informer::new(&client, "/config/").await?;
+/// println!("got {} keys", informer.snapshot().len());
+///
+/// while let Some(event) = informer.next().await {
+///     println!("got change: {:?}", event?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Informer<S> {
+    cache: Cache,
+    revision: Arc<AtomicI64>,
+    events: UnboundedReceiver<Result<InformerEvent>>,
+    handle: JoinHandle<()>,
+    _service: std::marker::PhantomData<fn() -> S>,
+}
+
+impl<S> Informer<S>
+where
+    S: GrpcService + 'static,
+{
+    /// List and watch the keys under `prefix`.
+    pub async fn new(client: &Client<S>, prefix: impl Into<Vec<u8>>) -> Result<Self> {
+        Self::with_page_size(client, prefix, DEFAULT_PAGE_SIZE).await
+    }
+
+    /// List and watch the keys under `prefix`, listing at most `page_size` keys per request.
+    pub async fn with_page_size(
+        client: &Client<S>,
+        prefix: impl Into<Vec<u8>>,
+        page_size: i64,
+    ) -> Result<Self> {
+        let mut reflector = Reflector {
+            kv: client.kv.clone(),
+            watch: client.watch.clone(),
+            prefix: prefix.into(),
+            page_size,
+            cache: Cache::default(),
+            revision: Arc::new(AtomicI64::new(0)),
+        };
+
+        let (revision, kvs) = reflector.list().await?;
+        *reflector.cache.write().unwrap() = kvs;
+        reflector.revision.store(revision, Ordering::Release);
+
+        let cache = reflector.cache.clone();
+        let revision = reflector.revision.clone();
+        let (tx, events) = unbounded_channel();
+        let handle = tokio::spawn(reflector.run(tx));
+
+        Ok(Informer {
+            cache,
+            revision,
+            events,
+            handle,
+            _service: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<S> Informer<S> {
+    /// A copy of all the cached keys.
+    pub fn snapshot(&self) -> BTreeMap<Vec<u8>, pb::KeyValue> {
+        self.cache.read().unwrap().clone()
+    }
+
+    /// Get a cached key.
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<pb::KeyValue> {
+        self.cache.read().unwrap().get(key.as_ref()).cloned()
+    }
+
+    /// The revision the cache is synced to.
+    pub fn revision(&self) -> i64 {
+        self.revision.load(Ordering::Acquire)
+    }
+}
+
+impl<S> Stream for Informer<S> {
+    type Item = Result<InformerEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl<S> Drop for Informer<S> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl<S> fmt::Debug for Informer<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Informer")
+            .field("revision", &self.revision())
+            .finish()
+    }
+}
+
+struct Reflector<S> {
+    kv: KvClient<S>,
+    watch: WatchClient<S>,
+    prefix: Vec<u8>,
+    page_size: i64,
+    cache: Cache,
+    revision: Arc<AtomicI64>,
+}
+
+impl<S> Reflector<S>
+where
+    S: GrpcService,
+{
+    /// Watch changes, and relist once the watch failed, until the informer is dropped.
+    async fn run(mut self, tx: UnboundedSender<Result<InformerEvent>>) {
+        let mut backoff = Backoff::default();
+
+        loop {
+            match self.watch(&tx).await {
+                Ok(()) => return,
+                Err(err) if err.kind() == ErrKind::Compacted => {
+                    tracing::debug!(?err, "informer revision compacted, relist");
+                }
+                Err(err) => {
+                    tracing::warn!(?err, "informer watch failed, relist");
+                    if tx.send(Err(err)).is_err() {
+                        return;
+                    }
+                    tokio::time::sleep(backoff.next_delay()).await;
+                }
+            }
+
+            match self.relist(&tx).await {
+                Ok(()) => backoff.reset(),
+                Err(err) => {
+                    tracing::warn!(?err, "informer relist failed");
+                    if tx.send(Err(err)).is_err() {
+                        return;
+                    }
+                    tokio::time::sleep(backoff.next_delay()).await;
+                }
+            }
+        }
+    }
+
+    /// List all keys page by page at the revision of the first page.
+    async fn list(&mut self) -> Result<(i64, BTreeMap<Vec<u8>, pb::KeyValue>)> {
+        let request = pb::RangeRequest {
+            limit: self.page_size,
+            ..pb::RangeRequest::new(self.prefix.clone()).with_prefix()
+        };
+        let mut pages = std::pin::pin!(range_pages(self.kv.clone(), request));
+
+        let mut revision = 0;
+        let mut kvs = BTreeMap::new();
+        while let Some(resp) = pages.next().await {
+            let resp = resp?;
+            if revision == 0 {
+                revision = resp.header.map(|h| h.revision).unwrap_or_default();
+            }
+            kvs.extend(resp.kvs.into_iter().map(|kv| (kv.key.clone(), kv)));
+        }
+
+        Ok((revision, kvs))
+    }
+
+    /// List again, replace the cache and yield the differences.
+    async fn relist(&mut self, tx: &UnboundedSender<Result<InformerEvent>>) -> Result<()> {
+        let (revision, kvs) = self.list().await?;
+
+        let events = {
+            let mut cache = self.cache.write().unwrap();
+            let events = diff(&cache, &kvs);
+            *cache = kvs;
+            events
+        };
+        self.revision.store(revision, Ordering::Release);
+
+        for event in events {
+            let _ = tx.send(Ok(event));
+        }
+
+        Ok(())
+    }
+
+    /// Watch from the next revision of the cache, returns `Ok` once the informer is dropped.
+    async fn watch(&mut self, tx: &UnboundedSender<Result<InformerEvent>>) -> Result<()> {
+        let revision = self.revision.load(Ordering::Acquire);
+        let mut watcher = self
+            .watch
+            .do_watch(self.prefix.clone())
+            .with_prefix()
+            .with_start_revision(revision + 1)
+            .resumable()
+            .await?;
+
+        while let Some(resp) = watcher.message().await? {
+            let mut events = Vec::with_capacity(resp.events.len());
+            {
+                let mut cache = self.cache.write().unwrap();
+                for ev in resp.events {
+                    let Some(kv) = ev.kv else {
+                        continue;
+                    };
+
+                    if ev.r#type == pb::event::EventType::Delete as i32 {
+                        if let Some(old) = cache.remove(&kv.key) {
+                            events.push(InformerEvent::Deleted(old));
+                        }
+                    } else {
+                        match cache.insert(kv.key.clone(), kv.clone()) {
+                            Some(old) => events.push(InformerEvent::Updated { old, new: kv }),
+                            None => events.push(InformerEvent::Added(kv)),
+                        }
+                    }
+                }
+            }
+            self.revision.store(watcher.revision(), Ordering::Release);
+
+            for event in events {
+                if tx.send(Ok(event)).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The changes from the `old` keys to the `new` keys, deletions first.
+fn diff(
+    old: &BTreeMap<Vec<u8>, pb::KeyValue>,
+    new: &BTreeMap<Vec<u8>, pb::KeyValue>,
+) -> Vec<InformerEvent> {
+    let mut events = Vec::new();
+    for (key, old) in old.iter() {
+        if !new.contains_key(key) {
+            events.push(InformerEvent::Deleted(old.clone()));
+        }
+    }
+    for (key, new) in new.iter() {
+        match old.get(key) {
+            Some(old) if old.mod_revision == new.mod_revision => {}
+            Some(old) => events.push(InformerEvent::Updated {
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            None => events.push(InformerEvent::Added(new.clone())),
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{InformerEvent, diff};
+    use crate::pb;
+
+    fn keys(kvs: &[(&str, i64)]) -> BTreeMap<Vec<u8>, pb::KeyValue> {
+        kvs.iter()
+            .map(|(key, revision)| {
+                let kv = pb::KeyValue {
+                    key: key.as_bytes().to_vec(),
+                    mod_revision: *revision,
+                    ..Default::default()
+                };
+                (kv.key.clone(), kv)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = keys(&[("a", 1), ("b", 2), ("c", 3)]);
+        let new = keys(&[("b", 2), ("c", 5), ("d", 6)]);
+
+        let events = diff(&old, &new);
+        assert_eq!(
+            events,
+            vec![
+                InformerEvent::Deleted(old[&b"a"[..]].clone()),
+                InformerEvent::Updated {
+                    old: old[&b"c"[..]].clone(),
+                    new: new[&b"c"[..]].clone(),
+                },
+                InformerEvent::Added(new[&b"d"[..]].clone()),
+            ]
+        );
+
+        assert!(diff(&new, &new).is_empty());
+    }
+}
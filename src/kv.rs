@@ -1,9 +1,18 @@
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
 use crate::error::{ErrKind, Error, Result};
 use crate::grpc::GrpcService;
 use crate::pb;
-use crate::utils::build_prefix_end;
+use crate::txn::{Txn, TxnResult, check_duplicate_keys};
+use crate::utils::{build_prefix_end, new_request, with_deadline};
+use futures::stream::{BoxStream, Stream, StreamExt};
 use tonic::IntoRequest;
 
+pub(crate) const DEFAULT_PAGE_SIZE: i64 = 1000;
+
 #[derive(Debug, Clone)]
 pub struct InnerKvClient<S> {
     service: S,
@@ -118,7 +127,7 @@ where
     /// # Ok(())
     /// # }
     /// ```
-    pub fn do_range(&mut self, key: impl Into<Vec<u8>>) -> DoRangeRequest<S> {
+    pub fn do_range(&mut self, key: impl Into<Vec<u8>>) -> DoRangeRequest<'_, S> {
         pb::RangeRequest::new(key).build(self)
     }
 
@@ -168,6 +177,38 @@ where
         Ok(resp.kvs)
     }
 
+    /// Get key-value pairs with prefix page by page.
+    ///
+    /// All pages are read at the revision of the first page, so the result is a consistent
+    /// snapshot.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error};
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let mut kvs = client.kv.range_stream("/huge/").with_page_size(500).with_keys_only();
+    ///
+    /// while let Some(kv) = kvs.next().await {
+    ///     println!("got key: {:?}", String::from_utf8_lossy(&kv?.key));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn range_stream(&self, prefix: impl Into<Vec<u8>>) -> RangeStream<S> {
+        let request = pb::RangeRequest {
+            limit: DEFAULT_PAGE_SIZE,
+            ..pb::RangeRequest::new(prefix).with_prefix()
+        };
+
+        RangeStream {
+            client: self.clone(),
+            request,
+            inner: None,
+        }
+    }
+
     /// Do put request
     ///
     /// ```no_run
@@ -182,7 +223,7 @@ where
         &mut self,
         key: impl Into<Vec<u8>>,
         value: impl Into<Vec<u8>>,
-    ) -> DoPutRequest<S> {
+    ) -> DoPutRequest<'_, S> {
         pb::PutRequest::new(key, value).build(self)
     }
 
@@ -205,7 +246,7 @@ where
     /// let resp = KvClient::new(client.service()).do_delete_range("hello").with_prefix().await.unwrap();
     /// # Ok(())
     /// # }
-    pub fn do_delete_range(&mut self, key: impl Into<Vec<u8>>) -> DoDeleteRangeRequest<S> {
+    pub fn do_delete_range(&mut self, key: impl Into<Vec<u8>>) -> DoDeleteRangeRequest<'_, S> {
         pb::DeleteRangeRequest::new(key).build(self)
     }
 
@@ -214,11 +255,190 @@ where
         self.do_delete_range(key).await.map(|_| ())
     }
 
-    pub fn do_txn(&mut self) -> DoTxnRequest<S> {
+    pub fn do_txn(&mut self) -> DoTxnRequest<'_, S> {
         pb::TxnRequest::default().build(self)
     }
 
-    pub fn do_compaction(&mut self, revision: i64, physical: bool) -> DoCompactionRequest<S> {
+    /// Commit a typed txn, the txn is checked for duplicate keys before sending.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error, Cmp, Op, Txn};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let txn = Txn::new()
+    ///     .when([Cmp::create_revision("/leader").eq(0)])
+    ///     .and_then([Op::put("/leader", "me")])
+    ///     .or_else([Op::get("/leader")]);
+    /// let result = client.kv.commit(txn).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn commit(&mut self, txn: Txn) -> Result<TxnResult> {
+        let request = pb::TxnRequest::from(txn);
+        check_duplicate_keys(&request)?;
+
+        self.txn(request).await.map(TxnResult::from)
+    }
+
+    /// Put `value` if the current value of the key is `expected`, `None` means the key does not
+    /// exist.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{CasResult, EtcdClient, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// match client.kv.compare_and_swap("/state", Some(b"idle"), "running").await? {
+    ///     CasResult::Succeeded(kv) => println!("started at {}", kv.mod_revision),
+    ///     CasResult::Failed(current) => println!("lost, current: {:?}", current),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn compare_and_swap(
+        &mut self,
+        key: impl Into<Vec<u8>>,
+        expected: Option<&[u8]>,
+        value: impl Into<Vec<u8>>,
+    ) -> Result<CasResult> {
+        use pb::compare::{CompareResult, TargetUnion};
+
+        let key = key.into();
+        let cmp = match expected {
+            Some(expected) => pb::Compare::new(
+                key.clone(),
+                CompareResult::Equal,
+                TargetUnion::Value(expected.to_vec()),
+            ),
+            None => pb::Compare::new(
+                key.clone(),
+                CompareResult::Equal,
+                TargetUnion::CreateRevision(0),
+            ),
+        };
+
+        self.put_if(key, cmp, value).await
+    }
+
+    /// Put the key-value pair if the key does not exist.
+    pub async fn put_if_absent(
+        &mut self,
+        key: impl Into<Vec<u8>>,
+        value: impl Into<Vec<u8>>,
+    ) -> Result<CasResult> {
+        self.compare_and_swap(key, None, value).await
+    }
+
+    /// Delete the key if its mod revision is `revision`, the deleted key-value pair is returned
+    /// on success.
+    pub async fn delete_if_revision(
+        &mut self,
+        key: impl Into<Vec<u8>>,
+        revision: i64,
+    ) -> Result<CasResult> {
+        use pb::compare::{CompareResult, TargetUnion};
+
+        let key = key.into();
+        let resp = self
+            .do_txn()
+            .with_if(vec![
+                pb::Compare::new(
+                    key.clone(),
+                    CompareResult::Greater,
+                    TargetUnion::CreateRevision(0),
+                ),
+                pb::Compare::new(
+                    key.clone(),
+                    CompareResult::Equal,
+                    TargetUnion::ModRevision(revision),
+                ),
+            ])
+            .with_then(vec![
+                pb::DeleteRangeRequest {
+                    prev_kv: true,
+                    ..pb::DeleteRangeRequest::new(key.clone())
+                }
+                .into(),
+            ])
+            .with_else(vec![pb::RangeRequest::new(key).into()])
+            .await?;
+
+        let current = resp
+            .responses
+            .into_iter()
+            .next()
+            .and_then(|op| match op.response? {
+                pb::response_op::Response::ResponseDeleteRange(resp) => {
+                    resp.prev_kvs.into_iter().next()
+                }
+                pb::response_op::Response::ResponseRange(resp) => resp.kvs.into_iter().next(),
+                _ => None,
+            });
+
+        CasResult::from_txn(resp.succeeded, current)
+    }
+
+    /// Update the value of the key with `f`, retried until no one else modified the key
+    /// in between. `f` gets `None` if the key does not exist.
+    ///
+    /// Returns the written key-value pair.
+    pub async fn update<F>(&mut self, key: impl Into<Vec<u8>>, mut f: F) -> Result<pb::KeyValue>
+    where
+        F: FnMut(Option<&[u8]>) -> Vec<u8>,
+    {
+        use pb::compare::{CompareResult, TargetUnion};
+
+        let key = key.into();
+        let mut current = self.do_range(key.clone()).await?.kvs.into_iter().next();
+
+        loop {
+            let value = f(current.as_ref().map(|kv| kv.value.as_slice()));
+            let revision = current
+                .as_ref()
+                .map(|kv| kv.mod_revision)
+                .unwrap_or_default();
+            let cmp = pb::Compare::new(
+                key.clone(),
+                CompareResult::Equal,
+                TargetUnion::ModRevision(revision),
+            );
+
+            match self.put_if(key.clone(), cmp, value).await? {
+                CasResult::Succeeded(kv) => return Ok(kv),
+                CasResult::Failed(kv) => current = kv,
+            }
+        }
+    }
+
+    /// Put the key-value pair if `cmp` succeeds, the written or current key-value pair is read
+    /// back in the same txn.
+    async fn put_if(
+        &mut self,
+        key: Vec<u8>,
+        cmp: pb::Compare,
+        value: impl Into<Vec<u8>>,
+    ) -> Result<CasResult> {
+        let resp = self
+            .do_txn()
+            .with_if(vec![cmp])
+            .with_then(vec![
+                pb::PutRequest::new(key.clone(), value).into(),
+                pb::RangeRequest::new(key.clone()).into(),
+            ])
+            .with_else(vec![pb::RangeRequest::new(key).into()])
+            .await?;
+
+        let current = resp
+            .responses
+            .last()
+            .and_then(|op| op.as_range())
+            .and_then(|resp| resp.kvs.first().cloned());
+
+        CasResult::from_txn(resp.succeeded, current)
+    }
+
+    pub fn do_compaction(&mut self, revision: i64, physical: bool) -> DoCompactionRequest<'_, S> {
         pb::CompactionRequest::new(revision, physical).build(self)
     }
 
@@ -247,6 +467,7 @@ impl pb::RangeRequest {
         DoRangeRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -254,11 +475,18 @@ impl pb::RangeRequest {
 pub struct DoRangeRequest<'a, S> {
     pub request: pb::RangeRequest,
     pub(crate) client: &'a mut KvClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoRangeRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut KvClient<S>) -> Self {
         self.client = client;
         self
@@ -332,8 +560,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::RangeResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoRangeRequest { request, client } = self;
-        Box::pin(async move { client.range(request).await })
+        let DoRangeRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.range(request)).await
+        })
     }
 }
 impl pb::PutRequest {
@@ -349,6 +584,7 @@ impl pb::PutRequest {
         DoPutRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -356,11 +592,18 @@ impl pb::PutRequest {
 pub struct DoPutRequest<'a, S> {
     pub request: pb::PutRequest,
     pub(crate) client: &'a mut KvClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoPutRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut KvClient<S>) -> Self {
         self.client = client;
         self
@@ -399,8 +642,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::PutResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoPutRequest { request, client } = self;
-        Box::pin(async move { client.put(request).await })
+        let DoPutRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.put(request)).await
+        })
     }
 }
 
@@ -416,6 +666,7 @@ impl pb::DeleteRangeRequest {
         DoDeleteRangeRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -423,11 +674,18 @@ impl pb::DeleteRangeRequest {
 pub struct DoDeleteRangeRequest<'a, S> {
     pub request: pb::DeleteRangeRequest,
     pub(crate) client: &'a mut KvClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoDeleteRangeRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut KvClient<S>) -> Self {
         self.client = client;
         self
@@ -462,8 +720,137 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::DeleteRangeResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoDeleteRangeRequest { request, client } = self;
-        Box::pin(async move { client.delete_range(request).await })
+        let DoDeleteRangeRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.delete_range(request)).await
+        })
+    }
+}
+
+/// Range the keys page by page, `request.limit` is the page size.
+///
+/// The following pages are pinned to the revision of the first page.
+pub(crate) fn range_pages<S>(
+    client: KvClient<S>,
+    request: pb::RangeRequest,
+) -> impl Stream<Item = Result<pb::RangeResponse>>
+where
+    S: GrpcService,
+{
+    futures::stream::unfold(Some((client, request)), |state| async move {
+        let (mut client, mut request) = state?;
+
+        let resp = match client.range(request.clone()).await {
+            Ok(resp) => resp,
+            Err(err) => return Some((Err(err), None)),
+        };
+
+        if request.revision <= 0 {
+            request.revision = resp.header.map(|h| h.revision).unwrap_or_default();
+        }
+
+        let next = match resp.kvs.last() {
+            Some(last) if resp.more => {
+                let mut key = last.key.clone();
+                key.push(0);
+                request.key = key;
+                Some((client, request))
+            }
+            _ => None,
+        };
+
+        Some((Ok(resp), next))
+    })
+}
+
+/// A stream of key-value pairs, ranged page by page.
+#[must_use = "streams do nothing unless polled"]
+pub struct RangeStream<S> {
+    client: KvClient<S>,
+    request: pb::RangeRequest,
+    inner: Option<BoxStream<'static, Result<pb::KeyValue>>>,
+}
+
+impl<S> RangeStream<S> {
+    /// The max number of keys per page.
+    pub fn with_page_size(mut self, page_size: i64) -> Self {
+        self.request.limit = page_size;
+        self
+    }
+
+    /// Only get the keys, without the values.
+    pub fn with_keys_only(mut self) -> Self {
+        self.request.keys_only = true;
+        self
+    }
+
+    /// Range at the given revision, instead of the revision of the first page.
+    pub fn with_revision(mut self, revision: i64) -> Self {
+        self.request.revision = revision;
+        self
+    }
+}
+
+impl<S> Stream for RangeStream<S>
+where
+    S: GrpcService + Unpin + 'static,
+{
+    type Item = Result<pb::KeyValue>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = this.inner.get_or_insert_with(|| {
+            range_pages(this.client.clone(), this.request.clone())
+                .flat_map(|page| {
+                    let kvs: Vec<_> = match page {
+                        Ok(resp) => resp.kvs.into_iter().map(Ok).collect(),
+                        Err(err) => vec![Err(err)],
+                    };
+                    futures::stream::iter(kvs)
+                })
+                .boxed()
+        });
+
+        inner.poll_next_unpin(cx)
+    }
+}
+
+impl<S> fmt::Debug for RangeStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeStream")
+            .field("request", &self.request)
+            .finish()
+    }
+}
+
+/// The result of a conditional write, like [`KvClient::compare_and_swap`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CasResult {
+    /// The write won, with the written (or deleted) key-value pair.
+    Succeeded(pb::KeyValue),
+    /// The write lost, with the current key-value pair, `None` if the key does not exist.
+    Failed(Option<pb::KeyValue>),
+}
+
+impl CasResult {
+    pub fn succeeded(&self) -> bool {
+        matches!(self, CasResult::Succeeded(_))
+    }
+
+    fn from_txn(succeeded: bool, kv: Option<pb::KeyValue>) -> Result<Self> {
+        match (succeeded, kv) {
+            (true, Some(kv)) => Ok(CasResult::Succeeded(kv)),
+            (true, None) => Err(Error::new(
+                ErrKind::InvalidData,
+                "missing key-value pair of succeeded txn",
+            )),
+            (false, kv) => Ok(CasResult::Failed(kv)),
+        }
     }
 }
 
@@ -565,6 +952,7 @@ impl pb::TxnRequest {
         DoTxnRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -572,11 +960,18 @@ impl pb::TxnRequest {
 pub struct DoTxnRequest<'a, S> {
     pub request: pb::TxnRequest,
     pub(crate) client: &'a mut KvClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoTxnRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_if(mut self, cmps: Vec<pb::Compare>) -> Self {
         self.request = self.request.with_if(cmps);
         self
@@ -606,8 +1001,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::TxnResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoTxnRequest { request, client } = self;
-        Box::pin(async move { client.txn(request).await })
+        let DoTxnRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.txn(request)).await
+        })
     }
 }
 impl pb::CompactionRequest {
@@ -619,6 +1021,7 @@ impl pb::CompactionRequest {
         DoCompactionRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -626,11 +1029,18 @@ impl pb::CompactionRequest {
 pub struct DoCompactionRequest<'a, S> {
     pub request: pb::CompactionRequest,
     pub(crate) client: &'a mut KvClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoCompactionRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut KvClient<S>) -> Self {
         self.client = client;
         self
@@ -653,7 +1063,262 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::CompactionResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoCompactionRequest { request, client } = self;
-        Box::pin(async move { client.compact(request).await })
+        let DoCompactionRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.compact(request)).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::StreamExt;
+
+    use super::{CasResult, KvClient};
+    use crate::ErrKind;
+    use crate::grpc::StubService;
+    use crate::pb;
+    use crate::pb::compare::{CompareResult, TargetUnion};
+    use crate::pb::request_op::Request;
+
+    fn page(revision: i64, keys: &[&str], more: bool) -> pb::RangeResponse {
+        pb::RangeResponse {
+            header: Some(pb::ResponseHeader {
+                revision,
+                ..Default::default()
+            }),
+            kvs: keys
+                .iter()
+                .map(|key| pb::KeyValue {
+                    key: key.as_bytes().to_vec(),
+                    ..Default::default()
+                })
+                .collect(),
+            more,
+            count: keys.len() as i64,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_range_stream() {
+        let service = StubService::default();
+        service.respond(page(10, &["/a/1", "/a/2"], true));
+        service.respond(page(12, &["/a/3"], false));
+
+        let client = KvClient::new(service.clone());
+        let kvs: Vec<_> = client
+            .range_stream("/a/")
+            .with_page_size(2)
+            .with_keys_only()
+            .map(|kv| kv.unwrap().key)
+            .collect()
+            .await;
+        assert_eq!(
+            kvs,
+            vec![b"/a/1".to_vec(), b"/a/2".to_vec(), b"/a/3".to_vec()]
+        );
+
+        let requests = service.requests::<pb::RangeRequest>();
+        assert_eq!(requests.len(), 2);
+        for req in &requests {
+            assert_eq!(req.limit, 2);
+            assert!(req.keys_only);
+            assert_eq!(req.range_end, b"/a0".to_vec());
+        }
+        assert_eq!(requests[0].key, b"/a/".to_vec());
+        assert_eq!(requests[0].revision, 0);
+        // the next page starts right after the last key, at the revision of the first page.
+        assert_eq!(requests[1].key, b"/a/2\0".to_vec());
+        assert_eq!(requests[1].revision, 10);
+    }
+
+    #[tokio::test]
+    async fn test_range_stream_with_revision() {
+        let service = StubService::default();
+        service.respond(page(12, &["/a/1"], true));
+        service.respond(page(12, &[], false));
+
+        let client = KvClient::new(service.clone());
+        let kvs: Vec<_> = client.range_stream("/a/").with_revision(5).collect().await;
+        assert_eq!(kvs.len(), 1);
+
+        let requests = service.requests::<pb::RangeRequest>();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|req| req.revision == 5));
+        assert!(!requests[0].keys_only);
+    }
+
+    fn kv(key: &str, value: &str, revision: i64) -> pb::KeyValue {
+        pb::KeyValue {
+            key: key.as_bytes().to_vec(),
+            value: value.as_bytes().to_vec(),
+            create_revision: 1,
+            mod_revision: revision,
+            ..Default::default()
+        }
+    }
+
+    /// A txn response whose last op is a range of `kv`.
+    fn txn(succeeded: bool, ops: usize, kv: Option<pb::KeyValue>) -> pb::TxnResponse {
+        let mut responses = vec![pb::ResponseOp::default(); ops - 1];
+        responses.push(pb::ResponseOp {
+            response: Some(pb::response_op::Response::ResponseRange(
+                pb::RangeResponse {
+                    kvs: kv.into_iter().collect(),
+                    ..Default::default()
+                },
+            )),
+        });
+        pb::TxnResponse {
+            succeeded,
+            responses,
+            ..Default::default()
+        }
+    }
+
+    fn cmp(key: &str, result: CompareResult, target: TargetUnion) -> pb::Compare {
+        pb::Compare::new(key, result, target)
+    }
+
+    #[tokio::test]
+    async fn test_compare_and_swap() {
+        let service = StubService::default();
+        service.respond(txn(true, 2, Some(kv("a", "2", 3))));
+        service.respond(txn(false, 1, Some(kv("a", "3", 4))));
+        service.respond(txn(false, 1, None));
+
+        let mut client = KvClient::new(service.clone());
+        let result = client.compare_and_swap("a", Some(b"1"), "2").await.unwrap();
+        assert_eq!(result, CasResult::Succeeded(kv("a", "2", 3)));
+        let result = client.compare_and_swap("a", Some(b"1"), "2").await.unwrap();
+        assert_eq!(result, CasResult::Failed(Some(kv("a", "3", 4))));
+        let result = client.put_if_absent("a", "1").await.unwrap();
+        assert_eq!(result, CasResult::Failed(None));
+
+        let requests = service.requests::<pb::TxnRequest>();
+        assert_eq!(
+            requests[0].compare,
+            vec![cmp(
+                "a",
+                CompareResult::Equal,
+                TargetUnion::Value(b"1".to_vec())
+            )]
+        );
+        assert_eq!(
+            requests[0].success,
+            vec![
+                pb::PutRequest::new("a", "2").into(),
+                pb::RangeRequest::new("a").into(),
+            ]
+        );
+        assert_eq!(requests[0].failure, vec![pb::RangeRequest::new("a").into()]);
+        assert_eq!(
+            requests[2].compare,
+            vec![cmp(
+                "a",
+                CompareResult::Equal,
+                TargetUnion::CreateRevision(0)
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_if_revision() {
+        let deleted = pb::TxnResponse {
+            succeeded: true,
+            responses: vec![pb::ResponseOp {
+                response: Some(pb::response_op::Response::ResponseDeleteRange(
+                    pb::DeleteRangeResponse {
+                        deleted: 1,
+                        prev_kvs: vec![kv("a", "1", 5)],
+                        ..Default::default()
+                    },
+                )),
+            }],
+            ..Default::default()
+        };
+        let service = StubService::default();
+        service.respond(deleted);
+        service.respond(txn(false, 1, Some(kv("a", "1", 6))));
+
+        let mut client = KvClient::new(service.clone());
+        let result = client.delete_if_revision("a", 5).await.unwrap();
+        assert_eq!(result, CasResult::Succeeded(kv("a", "1", 5)));
+        let result = client.delete_if_revision("a", 5).await.unwrap();
+        assert_eq!(result, CasResult::Failed(Some(kv("a", "1", 6))));
+
+        let request = &service.requests::<pb::TxnRequest>()[0];
+        assert_eq!(
+            request.compare,
+            vec![
+                cmp("a", CompareResult::Greater, TargetUnion::CreateRevision(0)),
+                cmp("a", CompareResult::Equal, TargetUnion::ModRevision(5)),
+            ]
+        );
+        let Some(Request::RequestDeleteRange(delete)) = &request.success[0].request else {
+            panic!("not a delete: {:?}", request.success);
+        };
+        assert!(delete.prev_kv);
+        assert_eq!(request.failure, vec![pb::RangeRequest::new("a").into()]);
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let service = StubService::default();
+        service.respond(pb::RangeResponse {
+            kvs: vec![kv("a", "1", 3)],
+            ..Default::default()
+        });
+        // modified by another writer in between.
+        service.respond(txn(false, 1, Some(kv("a", "5", 4))));
+        service.respond(txn(true, 2, Some(kv("a", "6", 7))));
+
+        let mut client = KvClient::new(service.clone());
+        let updated = client
+            .update("a", |old| {
+                let old: i64 = std::str::from_utf8(old.unwrap()).unwrap().parse().unwrap();
+                (old + 1).to_string().into_bytes()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated, kv("a", "6", 7));
+
+        let range: pb::RangeRequest = service.request(0);
+        assert_eq!(range, pb::RangeRequest::new("a"));
+        let txns: Vec<pb::TxnRequest> = (1..3).map(|i| service.request(i)).collect();
+        assert_eq!(
+            txns[0].compare,
+            vec![cmp("a", CompareResult::Equal, TargetUnion::ModRevision(3))]
+        );
+        assert_eq!(txns[0].success[0], pb::PutRequest::new("a", "2").into());
+        assert_eq!(
+            txns[1].compare,
+            vec![cmp("a", CompareResult::Equal, TargetUnion::ModRevision(4))]
+        );
+        assert_eq!(txns[1].success[0], pb::PutRequest::new("a", "6").into());
+    }
+
+    #[test]
+    fn test_cas_result_from_txn() {
+        let a = kv("a", "1", 1);
+        assert_eq!(
+            CasResult::from_txn(true, Some(a.clone())).unwrap(),
+            CasResult::Succeeded(a.clone())
+        );
+        assert_eq!(
+            CasResult::from_txn(false, Some(a.clone())).unwrap(),
+            CasResult::Failed(Some(a))
+        );
+        assert_eq!(
+            CasResult::from_txn(false, None).unwrap(),
+            CasResult::Failed(None)
+        );
+        let err = CasResult::from_txn(true, None).unwrap_err();
+        assert_eq!(err.kind(), ErrKind::InvalidData);
     }
 }
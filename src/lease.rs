@@ -1,15 +1,19 @@
 use std::fmt;
 use std::future::{Future, IntoFuture};
 use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
+use futures::Stream;
 use tokio::sync::mpsc::{Sender, channel};
 use tonic::IntoRequest;
 use tonic::IntoStreamingRequest;
 use tonic::codec::Streaming;
 
 use crate::error::{ErrKind, Error, Result};
-use crate::grpc::GrpcService;
+use crate::grpc::{GrpcService, StreamGuard};
 use crate::pb;
+use crate::utils::{new_request, with_deadline};
 
 const MPSC_CHANNEL_SIZE: usize = 1;
 
@@ -127,7 +131,7 @@ where
         }
     }
 
-    pub fn do_grant(&mut self, ttl: i64) -> DoLeaseGrantRequest<S> {
+    pub fn do_grant(&mut self, ttl: i64) -> DoLeaseGrantRequest<'_, S> {
         pb::LeaseGrantRequest::new(ttl, 0).build(self)
     }
 
@@ -153,7 +157,7 @@ where
 
     /// Keep the lease alive.
     #[must_use]
-    pub fn do_keep_alive(&mut self, lease_id: i64) -> DoLeaseKeepAlive<S> {
+    pub fn do_keep_alive(&mut self, lease_id: i64) -> DoLeaseKeepAlive<'_, S> {
         DoLeaseKeepAlive::new(lease_id, self)
     }
 
@@ -186,6 +190,7 @@ impl pb::LeaseGrantRequest {
         DoLeaseGrantRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -194,11 +199,18 @@ impl pb::LeaseGrantRequest {
 pub struct DoLeaseGrantRequest<'a, S> {
     pub request: pb::LeaseGrantRequest,
     pub(crate) client: &'a mut LeaseClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoLeaseGrantRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut LeaseClient<S>) -> Self {
         self.client = client;
         self
@@ -221,8 +233,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::LeaseGrantResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoLeaseGrantRequest { request, client } = self;
-        Box::pin(async move { client.lease_grant(request).await })
+        let DoLeaseGrantRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.lease_grant(request)).await
+        })
     }
 }
 
@@ -235,6 +254,7 @@ impl pb::LeaseRevokeRequest {
         DoLeaseRevokeRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -243,11 +263,18 @@ impl pb::LeaseRevokeRequest {
 pub struct DoLeaseRevokeRequest<'a, S> {
     pub request: pb::LeaseRevokeRequest,
     pub(crate) client: &'a mut LeaseClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoLeaseRevokeRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut LeaseClient<S>) -> Self {
         self.client = client;
         self
@@ -266,8 +293,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::LeaseRevokeResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoLeaseRevokeRequest { request, client } = self;
-        Box::pin(async move { client.lease_revoke(request).await })
+        let DoLeaseRevokeRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.lease_revoke(request)).await
+        })
     }
 }
 
@@ -285,6 +319,7 @@ impl pb::LeaseTimeToLiveRequest {
         DoLeaseTimeToLiveRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -293,11 +328,18 @@ impl pb::LeaseTimeToLiveRequest {
 pub struct DoLeaseTimeToLiveRequest<'a, S> {
     pub request: pb::LeaseTimeToLiveRequest,
     pub(crate) client: &'a mut LeaseClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoLeaseTimeToLiveRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut LeaseClient<S>) -> Self {
         self.client = client;
         self
@@ -324,8 +366,15 @@ where
         >,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoLeaseTimeToLiveRequest { request, client } = self;
-        Box::pin(async move { client.lease_time_to_live(request).await })
+        let DoLeaseTimeToLiveRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.lease_time_to_live(request)).await
+        })
     }
 }
 
@@ -334,6 +383,7 @@ impl pb::LeaseLeasesRequest {
         DoLeaseLeasesRequest {
             request: self,
             client,
+            timeout: None,
         }
     }
 }
@@ -341,11 +391,18 @@ impl pb::LeaseLeasesRequest {
 pub struct DoLeaseLeasesRequest<'a, S> {
     pub request: pb::LeaseLeasesRequest,
     pub(crate) client: &'a mut LeaseClient<S>,
+    pub(crate) timeout: Option<Duration>,
 }
 impl<'a, S> DoLeaseLeasesRequest<'a, S>
 where
     S: GrpcService,
 {
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_client(mut self, client: &'a mut LeaseClient<S>) -> Self {
         self.client = client;
         self
@@ -360,8 +417,15 @@ where
         Box<dyn std::future::Future<Output = crate::error::Result<pb::LeaseLeasesResponse>> + 'a>,
     >;
     fn into_future(self) -> Self::IntoFuture {
-        let DoLeaseLeasesRequest { request, client } = self;
-        Box::pin(async move { client.lease_leases(request).await })
+        let DoLeaseLeasesRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.lease_leases(request)).await
+        })
     }
 }
 
@@ -390,9 +454,16 @@ where
             .map_err(|err| Error::new(ErrKind::LeaseRequestFailed, err))?;
 
         let rx = tokio_stream::wrappers::ReceiverStream::new(req_rx);
-        let resp = client.lease_keep_alive(rx.into_streaming_request()).await?;
+        let mut resp = client
+            .inner
+            .lease_keep_alive(rx.into_streaming_request())
+            .await?;
+        let guard = StreamGuard::take(&mut resp);
 
-        Ok(LeaseKeepAliver::new(lease_id, req_tx, resp))
+        let mut keep_aliver = LeaseKeepAliver::new(lease_id, req_tx, resp.into_inner());
+        keep_aliver.guard = guard;
+
+        Ok(keep_aliver)
     }
 }
 
@@ -409,7 +480,7 @@ where
     S: GrpcService,
 {
     type Output = Result<LeaseKeepAliver>;
-    type IntoFuture = Pin<Box<dyn Future<Output = Result<LeaseKeepAliver>> + 'a>>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<LeaseKeepAliver>> + Send + 'a>>;
 
     fn into_future(self) -> Self::IntoFuture {
         Box::pin(self.send())
@@ -420,6 +491,7 @@ pub struct LeaseKeepAliver {
     lease_id: i64,
     req_tx: Sender<pb::LeaseKeepAliveRequest>,
     inbound: Streaming<crate::pb::LeaseKeepAliveResponse>,
+    guard: StreamGuard,
 }
 
 impl LeaseKeepAliver {
@@ -432,6 +504,7 @@ impl LeaseKeepAliver {
             lease_id,
             req_tx,
             inbound,
+            guard: StreamGuard::default(),
         }
     }
 
@@ -454,6 +527,16 @@ impl LeaseKeepAliver {
     }
 }
 
+impl Stream for LeaseKeepAliver {
+    type Item = Result<pb::LeaseKeepAliveResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inbound)
+            .poll_next(cx)
+            .map(|item| item.map(|resp| resp.map_err(Into::into)))
+    }
+}
+
 impl fmt::Debug for LeaseKeepAliver {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("LeaseKeepAliver")
@@ -30,18 +30,45 @@
 //! ```
 
 mod client;
+pub mod concurrency;
 mod error;
 pub mod grpc;
 pub mod pb;
 mod utils;
 
 mod auth;
+mod cluster;
+mod election;
+mod informer;
 mod kv;
 mod lease;
+mod lock;
+mod maintenance;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod namespace;
+mod retry;
+#[cfg(feature = "opentelemetry")]
+mod trace;
+mod txn;
 mod watch;
 
-pub use client::{Client, EtcdClient};
+#[cfg(feature = "tls")]
+pub use client::TlsOptions;
+pub use client::{Client, ClientBuilder, EtcdClient};
+pub use cluster::ClusterClient;
+pub use election::ElectionClient;
 pub use error::{ErrKind, Error};
-pub use kv::KvClient;
+pub use informer::{Informer, InformerEvent};
+pub use kv::{CasResult, KvClient, RangeStream};
 pub use lease::{LeaseClient, LeaseKeepAliver};
-pub use watch::{WatchClient, Watcher};
+pub use lock::LockClient;
+pub use maintenance::{AlarmMember, MaintenanceClient, Snapshot};
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+pub use namespace::Namespaced;
+pub use retry::Retry;
+#[cfg(feature = "opentelemetry")]
+pub use trace::Tracing;
+pub use txn::{Cmp, CmpTarget, Op, Txn, TxnResult};
+pub use watch::{ManagedWatcher, ResumableWatcher, WatchClient, WatchManager, Watcher};
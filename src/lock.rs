@@ -0,0 +1,231 @@
+use crate::error::Result;
+use crate::grpc::GrpcService;
+use crate::pb;
+use crate::utils::{new_request, with_deadline};
+
+use std::time::Duration;
+use tonic::IntoRequest;
+
+#[derive(Debug, Clone)]
+pub struct InnerLockClient<S> {
+    service: S,
+}
+impl<S> InnerLockClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+    pub async fn lock(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::LockRequest>,
+    ) -> Result<tonic::Response<pb::LockResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/v3lockpb.Lock/Lock");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn unlock(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UnlockRequest>,
+    ) -> Result<tonic::Response<pb::UnlockResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/v3lockpb.Lock/Unlock");
+        self.service.unary(request.into_request(), path).await
+    }
+}
+#[derive(Debug, Clone)]
+pub struct LockClient<S> {
+    inner: InnerLockClient<S>,
+}
+impl<S> LockClient<S>
+where
+    S: GrpcService,
+{
+    pub async fn lock(&mut self, request: pb::LockRequest) -> Result<pb::LockResponse> {
+        self.inner
+            .lock(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn unlock(&mut self, request: pb::UnlockRequest) -> Result<pb::UnlockResponse> {
+        self.inner
+            .unlock(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+}
+
+impl<S> LockClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        LockClient {
+            inner: InnerLockClient::new(service),
+        }
+    }
+
+    /// Do lock request, served by the v3lock service of etcd server.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error, LockClient};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let lease = client.grant_lease(10).await?;
+    /// let mut locker = LockClient::new(client.service());
+    /// let resp = locker.do_lock("my-lock").with_lease(lease.id).await?;
+    /// // do something exclusively
+    /// locker.release_lock(resp.key).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn do_lock(&mut self, name: impl Into<Vec<u8>>) -> DoLockRequest<'_, S> {
+        pb::LockRequest::new(name).build(self)
+    }
+
+    /// Acquire the lock `name` attached to `lease_id`, returns the lock ownership key.
+    pub async fn acquire_lock(
+        &mut self,
+        name: impl Into<Vec<u8>>,
+        lease_id: i64,
+    ) -> Result<Vec<u8>> {
+        let resp = self.do_lock(name).with_lease(lease_id).await?;
+        Ok(resp.key)
+    }
+
+    pub fn do_unlock(&mut self, key: impl Into<Vec<u8>>) -> DoUnlockRequest<'_, S> {
+        pb::UnlockRequest::new(key).build(self)
+    }
+
+    /// Release the lock by the lock ownership key.
+    pub async fn release_lock(&mut self, key: impl Into<Vec<u8>>) -> Result<()> {
+        self.do_unlock(key).await?;
+        Ok(())
+    }
+}
+
+impl pb::LockRequest {
+    pub fn new(name: impl Into<Vec<u8>>) -> Self {
+        pb::LockRequest {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut LockClient<S>) -> DoLockRequest<'_, S> {
+        DoLockRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoLockRequest<'a, S> {
+    pub request: pb::LockRequest,
+    pub(crate) client: &'a mut LockClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoLockRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut LockClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_name(mut self, name: Vec<u8>) -> Self {
+        self.request.name = name;
+        self
+    }
+    /// The lease attached to the lock ownership, the lock is released once the lease expires.
+    pub fn with_lease(mut self, lease: i64) -> Self {
+        self.request.lease = lease;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoLockRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::LockResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::LockResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoLockRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.lock(request)).await
+        })
+    }
+}
+
+impl pb::UnlockRequest {
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        pb::UnlockRequest { key: key.into() }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut LockClient<S>) -> DoUnlockRequest<'_, S> {
+        DoUnlockRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoUnlockRequest<'a, S> {
+    pub request: pb::UnlockRequest,
+    pub(crate) client: &'a mut LockClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoUnlockRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut LockClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_key(mut self, key: Vec<u8>) -> Self {
+        self.request.key = key;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoUnlockRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::UnlockResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::UnlockResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoUnlockRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.unlock(request)).await
+        })
+    }
+}
@@ -0,0 +1,1007 @@
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::{CredentialInterceptor, GrpcService, StreamGuard, TonicClient};
+use crate::pb;
+use crate::utils::{new_request, with_deadline};
+
+use std::fmt;
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::Stream;
+use http::Uri;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tonic::IntoRequest;
+use tonic::codec::Streaming;
+
+/// Size of the sha256 checksum which etcd appends to the snapshot.
+const SNAPSHOT_CHECKSUM_SIZE: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct InnerMaintenanceClient<S> {
+    service: S,
+}
+impl<S> InnerMaintenanceClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+    pub async fn alarm(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AlarmRequest>,
+    ) -> Result<tonic::Response<pb::AlarmResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/Alarm");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn status(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::StatusRequest>,
+    ) -> Result<tonic::Response<pb::StatusResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/Status");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn defragment(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::DefragmentRequest>,
+    ) -> Result<tonic::Response<pb::DefragmentResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/Defragment");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn hash(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::HashRequest>,
+    ) -> Result<tonic::Response<pb::HashResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/Hash");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn hash_kv(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::HashKvRequest>,
+    ) -> Result<tonic::Response<pb::HashKvResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/HashKV");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn move_leader(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::MoveLeaderRequest>,
+    ) -> Result<tonic::Response<pb::MoveLeaderResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/MoveLeader");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn downgrade(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::DowngradeRequest>,
+    ) -> Result<tonic::Response<pb::DowngradeResponse>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/Downgrade");
+        self.service.unary(request.into_request(), path).await
+    }
+    pub async fn snapshot(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::SnapshotRequest>,
+    ) -> Result<tonic::Response<tonic::codec::Streaming<pb::SnapshotResponse>>> {
+        let path = http::uri::PathAndQuery::from_static("/etcdserverpb.Maintenance/Snapshot");
+        self.service
+            .server_streaming(request.into_request(), path)
+            .await
+    }
+}
+#[derive(Debug, Clone)]
+pub struct MaintenanceClient<S> {
+    inner: InnerMaintenanceClient<S>,
+}
+impl<S> MaintenanceClient<S>
+where
+    S: GrpcService,
+{
+    pub async fn alarm(&mut self, request: pb::AlarmRequest) -> Result<pb::AlarmResponse> {
+        self.inner
+            .alarm(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn status(&mut self, request: pb::StatusRequest) -> Result<pb::StatusResponse> {
+        self.inner
+            .status(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn defragment(
+        &mut self,
+        request: pb::DefragmentRequest,
+    ) -> Result<pb::DefragmentResponse> {
+        self.inner
+            .defragment(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn hash(&mut self, request: pb::HashRequest) -> Result<pb::HashResponse> {
+        self.inner
+            .hash(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn hash_kv(&mut self, request: pb::HashKvRequest) -> Result<pb::HashKvResponse> {
+        self.inner
+            .hash_kv(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn move_leader(
+        &mut self,
+        request: pb::MoveLeaderRequest,
+    ) -> Result<pb::MoveLeaderResponse> {
+        self.inner
+            .move_leader(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn downgrade(
+        &mut self,
+        request: pb::DowngradeRequest,
+    ) -> Result<pb::DowngradeResponse> {
+        self.inner
+            .downgrade(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+    pub async fn snapshot(
+        &mut self,
+        request: pb::SnapshotRequest,
+    ) -> Result<tonic::codec::Streaming<pb::SnapshotResponse>> {
+        self.inner
+            .snapshot(request.into_request())
+            .await
+            .map(|rsp| rsp.into_inner())
+    }
+}
+
+impl<S> MaintenanceClient<S>
+where
+    S: GrpcService,
+{
+    pub fn new(service: S) -> Self {
+        MaintenanceClient {
+            inner: InnerMaintenanceClient::new(service),
+        }
+    }
+
+    /// Do alarm request
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error, MaintenanceClient, pb};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let resp = MaintenanceClient::new(client.service())
+    ///     .do_alarm(pb::alarm_request::AlarmAction::Get)
+    ///     .await
+    ///     .unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn do_alarm(&mut self, action: pb::alarm_request::AlarmAction) -> DoAlarmRequest<'_, S> {
+        pb::AlarmRequest::new(action).build(self)
+    }
+
+    /// List all active alarms of the cluster.
+    pub async fn list_alarms(&mut self) -> Result<Vec<AlarmMember>> {
+        let resp = self.do_alarm(pb::alarm_request::AlarmAction::Get).await?;
+        resp.alarms.into_iter().map(AlarmMember::try_from).collect()
+    }
+
+    /// Disarm the given alarm, returns the alarms which have been deactivated.
+    pub async fn disarm_alarm(&mut self, alarm: &AlarmMember) -> Result<Vec<AlarmMember>> {
+        let resp = self
+            .do_alarm(pb::alarm_request::AlarmAction::Deactivate)
+            .with_member_id(alarm.member_id)
+            .with_alarm(alarm.alarm)
+            .await?;
+        resp.alarms.into_iter().map(AlarmMember::try_from).collect()
+    }
+
+    /// Disarm all active alarms of the cluster, returns the alarms which have been deactivated.
+    pub async fn disarm_all_alarms(&mut self) -> Result<Vec<AlarmMember>> {
+        let mut disarmed = Vec::new();
+        for alarm in self.list_alarms().await? {
+            disarmed.extend(self.disarm_alarm(&alarm).await?);
+        }
+        Ok(disarmed)
+    }
+
+    pub fn do_status(&mut self) -> DoStatusRequest<'_, S> {
+        pb::StatusRequest::default().build(self)
+    }
+
+    /// Get the status of the member which serves the request.
+    pub async fn get_status(&mut self) -> Result<pb::StatusResponse> {
+        self.do_status().await
+    }
+
+    pub fn do_defragment(&mut self) -> DoDefragmentRequest<'_, S> {
+        pb::DefragmentRequest::default().build(self)
+    }
+
+    /// Defragment the backend database of the member which serves the request.
+    ///
+    /// Defragment is an expensive operation on a single member, use
+    /// [`MaintenanceClient::defragment_endpoint`] to pick the member explicitly.
+    pub async fn defragment_member(&mut self) -> Result<()> {
+        self.do_defragment().await.map(|_| ())
+    }
+
+    pub fn do_hash(&mut self) -> DoHashRequest<'_, S> {
+        pb::HashRequest::default().build(self)
+    }
+
+    /// Get the hash of the backend database of the member which serves the request.
+    pub async fn get_hash(&mut self) -> Result<u32> {
+        let resp = self.do_hash().await?;
+        Ok(resp.hash)
+    }
+
+    pub fn do_hash_kv(&mut self, revision: i64) -> DoHashKvRequest<'_, S> {
+        pb::HashKvRequest::new(revision).build(self)
+    }
+
+    /// Get the hash of the MVCC key-values up to the given revision, 0 means the latest revision.
+    pub async fn get_hash_kv(&mut self, revision: i64) -> Result<pb::HashKvResponse> {
+        self.do_hash_kv(revision).await
+    }
+
+    pub fn do_move_leader(&mut self, target_id: u64) -> DoMoveLeaderRequest<'_, S> {
+        pb::MoveLeaderRequest::new(target_id).build(self)
+    }
+
+    /// Transfer the leadership to the member with the given id.
+    pub async fn transfer_leader(&mut self, target_id: u64) -> Result<()> {
+        self.do_move_leader(target_id).await.map(|_| ())
+    }
+
+    pub fn do_downgrade(
+        &mut self,
+        action: pb::downgrade_request::DowngradeAction,
+        version: impl Into<String>,
+    ) -> DoDowngradeRequest<'_, S> {
+        pb::DowngradeRequest::new(action, version).build(self)
+    }
+
+    /// Validate whether the cluster can be downgraded to the given version.
+    pub async fn downgrade_validate(&mut self, version: impl Into<String>) -> Result<String> {
+        let resp = self
+            .do_downgrade(pb::downgrade_request::DowngradeAction::Validate, version)
+            .await?;
+        Ok(resp.version)
+    }
+
+    /// Start downgrading the cluster to the given version.
+    pub async fn downgrade_enable(&mut self, version: impl Into<String>) -> Result<String> {
+        let resp = self
+            .do_downgrade(pb::downgrade_request::DowngradeAction::Enable, version)
+            .await?;
+        Ok(resp.version)
+    }
+
+    /// Cancel the ongoing downgrade job.
+    pub async fn downgrade_cancel(&mut self) -> Result<String> {
+        let resp = self
+            .do_downgrade(pb::downgrade_request::DowngradeAction::Cancel, "")
+            .await?;
+        Ok(resp.version)
+    }
+
+    /// Do snapshot request
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error, MaintenanceClient};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let mut file = tokio::fs::File::create("backup.db").await.unwrap();
+    /// let snapshot = MaintenanceClient::new(client.service()).do_snapshot().await?;
+    /// snapshot
+    ///     .save(&mut file, |received, remaining| {
+    ///         println!("received {} bytes, {} bytes remaining", received, remaining)
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn do_snapshot(&mut self) -> DoSnapshot<'_, S> {
+        DoSnapshot::new(self)
+    }
+
+    /// Stream a snapshot of the backend database into `writer`, the checksum is verified.
+    ///
+    /// Returns the number of bytes written.
+    pub async fn save_snapshot<W>(
+        &mut self,
+        writer: &mut W,
+        progress: impl FnMut(u64, u64),
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.do_snapshot().await?.save(writer, progress).await
+    }
+}
+
+impl MaintenanceClient<CredentialInterceptor<TonicClient>> {
+    /// Create a MaintenanceClient which only talks to the given endpoint.
+    ///
+    /// The credential and the connection options of the current client are reused.
+    pub async fn for_endpoint<U>(&self, endpoint: U) -> Result<Self>
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let uri = Uri::try_from(endpoint).map_err(|err| Error::new(ErrKind::Endpoint, err))?;
+        let client = self.inner.service.inner().connect_to(uri).await?;
+        let service = self.inner.service.with_inner(client);
+
+        Ok(MaintenanceClient::new(service))
+    }
+
+    /// Get the status of the given endpoint.
+    pub async fn status_endpoint<U>(&self, endpoint: U) -> Result<pb::StatusResponse>
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        self.for_endpoint(endpoint).await?.get_status().await
+    }
+
+    /// Defragment the backend database of the given endpoint.
+    pub async fn defragment_endpoint<U>(&self, endpoint: U) -> Result<()>
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        self.for_endpoint(endpoint).await?.defragment_member().await
+    }
+}
+
+/// An alarm raised on a member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlarmMember {
+    /// ID of the member associated with the raised alarm.
+    pub member_id: u64,
+    /// Type of the raised alarm.
+    pub alarm: pb::AlarmType,
+}
+
+impl TryFrom<pb::AlarmMember> for AlarmMember {
+    type Error = Error;
+
+    fn try_from(member: pb::AlarmMember) -> Result<Self> {
+        let alarm = pb::AlarmType::try_from(member.alarm)
+            .map_err(|err| Error::new(ErrKind::InvalidData, err))?;
+
+        Ok(AlarmMember {
+            member_id: member.member_id,
+            alarm,
+        })
+    }
+}
+
+impl From<AlarmMember> for pb::AlarmMember {
+    fn from(member: AlarmMember) -> Self {
+        pb::AlarmMember {
+            member_id: member.member_id,
+            alarm: member.alarm.into(),
+        }
+    }
+}
+
+impl pb::AlarmRequest {
+    pub fn new(action: pb::alarm_request::AlarmAction) -> Self {
+        pb::AlarmRequest {
+            action: action.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn build<S: GrpcService>(self, client: &mut MaintenanceClient<S>) -> DoAlarmRequest<'_, S> {
+        DoAlarmRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoAlarmRequest<'a, S> {
+    pub request: pb::AlarmRequest,
+    pub(crate) client: &'a mut MaintenanceClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoAlarmRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut MaintenanceClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_action(mut self, action: pb::alarm_request::AlarmAction) -> Self {
+        self.request.action = action.into();
+        self
+    }
+    /// Set the member of the alarm, 0 means all members.
+    pub fn with_member_id(mut self, member_id: u64) -> Self {
+        self.request.member_id = member_id;
+        self
+    }
+    pub fn with_alarm(mut self, alarm: pb::AlarmType) -> Self {
+        self.request.alarm = alarm.into();
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoAlarmRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::AlarmResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::AlarmResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoAlarmRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.alarm(request)).await
+        })
+    }
+}
+
+impl pb::StatusRequest {
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut MaintenanceClient<S>,
+    ) -> DoStatusRequest<'_, S> {
+        DoStatusRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoStatusRequest<'a, S> {
+    pub request: pb::StatusRequest,
+    pub(crate) client: &'a mut MaintenanceClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoStatusRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut MaintenanceClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoStatusRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::StatusResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::StatusResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoStatusRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.status(request)).await
+        })
+    }
+}
+
+impl pb::DefragmentRequest {
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut MaintenanceClient<S>,
+    ) -> DoDefragmentRequest<'_, S> {
+        DoDefragmentRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoDefragmentRequest<'a, S> {
+    pub request: pb::DefragmentRequest,
+    pub(crate) client: &'a mut MaintenanceClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoDefragmentRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut MaintenanceClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoDefragmentRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::DefragmentResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::DefragmentResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoDefragmentRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.defragment(request)).await
+        })
+    }
+}
+
+impl pb::HashRequest {
+    pub fn build<S: GrpcService>(self, client: &mut MaintenanceClient<S>) -> DoHashRequest<'_, S> {
+        DoHashRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoHashRequest<'a, S> {
+    pub request: pb::HashRequest,
+    pub(crate) client: &'a mut MaintenanceClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoHashRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut MaintenanceClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoHashRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::HashResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::HashResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoHashRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.hash(request)).await
+        })
+    }
+}
+
+impl pb::HashKvRequest {
+    pub fn new(revision: i64) -> Self {
+        pb::HashKvRequest { revision }
+    }
+
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut MaintenanceClient<S>,
+    ) -> DoHashKvRequest<'_, S> {
+        DoHashKvRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoHashKvRequest<'a, S> {
+    pub request: pb::HashKvRequest,
+    pub(crate) client: &'a mut MaintenanceClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoHashKvRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut MaintenanceClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_revision(mut self, revision: i64) -> Self {
+        self.request.revision = revision;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoHashKvRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::HashKvResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::HashKvResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoHashKvRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.hash_kv(request)).await
+        })
+    }
+}
+
+impl pb::MoveLeaderRequest {
+    pub fn new(target_id: u64) -> Self {
+        pb::MoveLeaderRequest { target_id }
+    }
+
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut MaintenanceClient<S>,
+    ) -> DoMoveLeaderRequest<'_, S> {
+        DoMoveLeaderRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoMoveLeaderRequest<'a, S> {
+    pub request: pb::MoveLeaderRequest,
+    pub(crate) client: &'a mut MaintenanceClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoMoveLeaderRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut MaintenanceClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_target_id(mut self, target_id: u64) -> Self {
+        self.request.target_id = target_id;
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoMoveLeaderRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::MoveLeaderResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::MoveLeaderResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoMoveLeaderRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.move_leader(request)).await
+        })
+    }
+}
+
+impl pb::DowngradeRequest {
+    pub fn new(action: pb::downgrade_request::DowngradeAction, version: impl Into<String>) -> Self {
+        pb::DowngradeRequest {
+            action: action.into(),
+            version: version.into(),
+        }
+    }
+
+    pub fn build<S: GrpcService>(
+        self,
+        client: &mut MaintenanceClient<S>,
+    ) -> DoDowngradeRequest<'_, S> {
+        DoDowngradeRequest {
+            request: self,
+            client,
+            timeout: None,
+        }
+    }
+}
+#[must_use]
+pub struct DoDowngradeRequest<'a, S> {
+    pub request: pb::DowngradeRequest,
+    pub(crate) client: &'a mut MaintenanceClient<S>,
+    pub(crate) timeout: Option<Duration>,
+}
+impl<'a, S> DoDowngradeRequest<'a, S>
+where
+    S: GrpcService,
+{
+    /// Set the timeout of the request, overriding the default timeout of the client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_client(mut self, client: &'a mut MaintenanceClient<S>) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn with_action(mut self, action: pb::downgrade_request::DowngradeAction) -> Self {
+        self.request.action = action.into();
+        self
+    }
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.request.version = version.into();
+        self
+    }
+}
+impl<'a, S> std::future::IntoFuture for DoDowngradeRequest<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<pb::DowngradeResponse>;
+    type IntoFuture = std::pin::Pin<
+        Box<dyn std::future::Future<Output = crate::error::Result<pb::DowngradeResponse>> + 'a>,
+    >;
+    fn into_future(self) -> Self::IntoFuture {
+        let DoDowngradeRequest {
+            request,
+            client,
+            timeout,
+        } = self;
+        Box::pin(async move {
+            let request = new_request(request, timeout);
+            with_deadline(timeout, client.inner.downgrade(request)).await
+        })
+    }
+}
+
+#[must_use]
+pub struct DoSnapshot<'a, S> {
+    pub request: pb::SnapshotRequest,
+    client: &'a mut MaintenanceClient<S>,
+}
+
+impl<'a, S> DoSnapshot<'a, S>
+where
+    S: GrpcService,
+{
+    pub fn new(client: &'a mut MaintenanceClient<S>) -> Self {
+        DoSnapshot {
+            request: pb::SnapshotRequest::default(),
+            client,
+        }
+    }
+
+    async fn send(self) -> Result<Snapshot> {
+        let DoSnapshot { request, client } = self;
+
+        let mut resp = client.inner.snapshot(request.into_request()).await?;
+        let guard = StreamGuard::take(&mut resp);
+
+        Ok(Snapshot::new(resp.into_inner(), guard))
+    }
+}
+
+impl<'a, S> fmt::Debug for DoSnapshot<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DoSnapshot")
+            .field("request", &self.request)
+            .finish()
+    }
+}
+
+impl<'a, S> IntoFuture for DoSnapshot<'a, S>
+where
+    S: GrpcService,
+{
+    type Output = Result<Snapshot>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<Snapshot>> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.send())
+    }
+}
+
+/// A stream of snapshot chunks.
+///
+/// The last chunk sent by etcd is the sha256 checksum of the preceding data.
+pub struct Snapshot {
+    inbound: Streaming<pb::SnapshotResponse>,
+    /// Held as long as the stream.
+    _guard: StreamGuard,
+}
+
+impl Snapshot {
+    pub(crate) fn new(inbound: Streaming<pb::SnapshotResponse>, guard: StreamGuard) -> Self {
+        Snapshot {
+            inbound,
+            _guard: guard,
+        }
+    }
+
+    pub async fn message(&mut self) -> Result<Option<pb::SnapshotResponse>> {
+        Ok(self.inbound.message().await?)
+    }
+
+    /// Write all chunks into `writer`, calls `progress` with the received and remaining bytes
+    /// after every chunk.
+    ///
+    /// The trailing checksum is written too, so the output can be restored by `etcdutl`.
+    /// Returns the number of bytes written after the checksum has been verified.
+    pub async fn save<W>(
+        mut self,
+        writer: &mut W,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut verifier = ChecksumVerifier::default();
+        let mut received = 0;
+
+        while let Some(resp) = self.message().await? {
+            writer
+                .write_all(&resp.blob)
+                .await
+                .map_err(|err| Error::new(ErrKind::SnapshotFailed, err))?;
+
+            verifier.update(&resp.blob);
+            received += resp.blob.len() as u64;
+            progress(received, resp.remaining_bytes);
+        }
+
+        writer
+            .flush()
+            .await
+            .map_err(|err| Error::new(ErrKind::SnapshotFailed, err))?;
+
+        verifier.verify()?;
+
+        Ok(received)
+    }
+}
+
+impl Stream for Snapshot {
+    type Item = Result<pb::SnapshotResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inbound)
+            .poll_next(cx)
+            .map(|item| item.map(|resp| resp.map_err(Into::into)))
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot").finish()
+    }
+}
+
+/// Hash all received data except the trailing checksum.
+#[derive(Default)]
+struct ChecksumVerifier {
+    hasher: Sha256,
+    tail: Vec<u8>,
+}
+
+impl ChecksumVerifier {
+    fn update(&mut self, data: &[u8]) {
+        self.tail.extend_from_slice(data);
+
+        if self.tail.len() > SNAPSHOT_CHECKSUM_SIZE {
+            let n = self.tail.len() - SNAPSHOT_CHECKSUM_SIZE;
+            self.hasher.update(&self.tail[..n]);
+            self.tail.drain(..n);
+        }
+    }
+
+    fn verify(self) -> Result<()> {
+        if self.tail.len() != SNAPSHOT_CHECKSUM_SIZE {
+            return Err(Error::new(
+                ErrKind::SnapshotCorrupted,
+                "snapshot checksum is missing",
+            ));
+        }
+
+        if self.hasher.finalize().as_slice() != self.tail.as_slice() {
+            return Err(Error::new(
+                ErrKind::SnapshotCorrupted,
+                "snapshot checksum mismatch",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChecksumVerifier;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_checksum_verifier() {
+        let data = b"etcd snapshot data".repeat(10);
+        let checksum = Sha256::digest(&data);
+
+        let mut verifier = ChecksumVerifier::default();
+        for chunk in data.chunks(7) {
+            verifier.update(chunk);
+        }
+        verifier.update(&checksum);
+        assert!(verifier.verify().is_ok());
+
+        let mut verifier = ChecksumVerifier::default();
+        verifier.update(&data[1..]);
+        verifier.update(&checksum);
+        assert!(verifier.verify().is_err());
+
+        let mut verifier = ChecksumVerifier::default();
+        verifier.update(&checksum[1..]);
+        assert!(verifier.verify().is_err());
+    }
+}
@@ -0,0 +1,305 @@
+use std::time::Instant;
+
+use ::metrics::{counter, gauge, histogram};
+use http::uri::PathAndQuery;
+use tonic::codec::Streaming;
+
+use crate::error::{Error, Result};
+use crate::grpc::{GrpcService, StreamGuard, StreamObserver};
+
+const REQUESTS_TOTAL: &str = "etcd_client_requests_total";
+const ERRORS_TOTAL: &str = "etcd_client_errors_total";
+const REQUEST_DURATION_SECONDS: &str = "etcd_client_request_duration_seconds";
+const ACTIVE_STREAMS: &str = "etcd_client_active_streams";
+const WATCH_EVENTS_TOTAL: &str = "etcd_client_watch_events_total";
+
+/// The error kind of requests dropped before finished.
+const CANCELLED: &str = "Cancelled";
+
+/// A service recording the metrics of each request, with the [`metrics`](::metrics) crate.
+///
+/// The metrics are labeled by the gRPC method, like `/etcdserverpb.KV/Range`:
+///
+/// - `etcd_client_requests_total`, the count of requests.
+/// - `etcd_client_errors_total`, the count of failed requests, also labeled by the `ErrKind`, or
+///   `Cancelled` if the request is dropped before finished, like timed out by `with_timeout`.
+/// - `etcd_client_request_duration_seconds`, the histogram of latencies, for streams it is the
+///   latency of establishing the stream.
+/// - `etcd_client_active_streams`, the gauge of open streams held by a [`Watcher`], a
+///   [`WatchManager`], a [`LeaseKeepAliver`] or a [`Snapshot`], the raw streams returned by
+///   methods like [`WatchClient::watch`] are not counted.
+/// - `etcd_client_watch_events_total`, the count of watch events received by them, without labels.
+///
+///
+/// [`Watcher`]: crate::Watcher
+/// [`WatchManager`]: crate::WatchManager
+/// [`LeaseKeepAliver`]: crate::LeaseKeepAliver
+/// [`Snapshot`]: crate::Snapshot
+/// [`WatchClient::watch`]: crate::WatchClient::watch
+///
+/// ```no_run
+/// # use etcdv3client::{Client, EtcdClient, Error, Metrics};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let mut client = Client::with_service(Metrics::new(client.service()));
+///
+/// let value = client.get("/hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metrics<C> {
+    inner: C,
+}
+
+impl<C> Metrics<C>
+where
+    C: GrpcService,
+{
+    pub fn new(inner: C) -> Self {
+        Metrics { inner }
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C> GrpcService for Metrics<C>
+where
+    C: GrpcService,
+{
+    async fn unary<M, T>(
+        &mut self,
+        req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut request = Request::start(&path);
+        let result = self.inner.unary(req, path).await;
+        request.finish(result.as_ref().err());
+
+        result
+    }
+
+    async fn client_streaming<S, M, T>(
+        &mut self,
+        req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut request = Request::start(&path);
+        let result = self.inner.client_streaming(req, path).await;
+        request.finish(result.as_ref().err());
+
+        result
+    }
+
+    async fn server_streaming<M, T>(
+        &mut self,
+        req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<Streaming<T>>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut request = Request::start(&path);
+        let mut result = self.inner.server_streaming(req, path).await;
+        request.finish(result.as_ref().err());
+
+        if let Ok(resp) = &mut result {
+            StreamGuard::attach(resp, ActiveStream::new(request.method.clone()));
+        }
+        result
+    }
+
+    async fn streaming<S, M, T>(
+        &mut self,
+        req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<Streaming<T>>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut request = Request::start(&path);
+        let mut result = self.inner.streaming(req, path).await;
+        request.finish(result.as_ref().err());
+
+        if let Ok(resp) = &mut result {
+            StreamGuard::attach(resp, ActiveStream::new(request.method.clone()));
+        }
+        result
+    }
+}
+
+/// The metrics of a request in flight.
+///
+/// If it is dropped before finished, like timed out by `with_timeout` of the request, the
+/// request is counted as failed with the `Cancelled` kind.
+struct Request {
+    method: String,
+    start: Instant,
+    finished: bool,
+}
+
+impl Request {
+    fn start(path: &PathAndQuery) -> Self {
+        let method = path.path().to_string();
+        counter!(REQUESTS_TOTAL, "method" => method.clone()).increment(1);
+
+        Request {
+            method,
+            start: Instant::now(),
+            finished: false,
+        }
+    }
+
+    fn finish(&mut self, err: Option<&Error>) {
+        self.record(err.map(|err| format!("{:?}", err.kind())));
+    }
+
+    fn record(&mut self, kind: Option<String>) {
+        self.finished = true;
+
+        histogram!(REQUEST_DURATION_SECONDS, "method" => self.method.clone())
+            .record(self.start.elapsed());
+
+        if let Some(kind) = kind {
+            counter!(ERRORS_TOTAL, "method" => self.method.clone(), "kind" => kind).increment(1);
+        }
+    }
+}
+
+impl Drop for Request {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.record(Some(CANCELLED.to_string()));
+        }
+    }
+}
+
+/// Count the stream as active until it is dropped by its holder, and count the watch events of it.
+struct ActiveStream {
+    gauge: ::metrics::Gauge,
+    events: ::metrics::Counter,
+}
+
+impl ActiveStream {
+    fn new(method: String) -> Self {
+        let gauge = gauge!(ACTIVE_STREAMS, "method" => method);
+        gauge.increment(1);
+        ActiveStream {
+            gauge,
+            events: counter!(WATCH_EVENTS_TOTAL),
+        }
+    }
+}
+
+impl StreamObserver for ActiveStream {
+    fn watch_events(&self, count: usize) {
+        self.events.increment(count as u64);
+    }
+}
+
+impl Drop for ActiveStream {
+    fn drop(&mut self) {
+        self.gauge.decrement(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use http::uri::PathAndQuery;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    use super::{ActiveStream, Request};
+    use crate::error::{ErrKind, Error};
+    use crate::grpc::StreamGuard;
+
+    /// Take the changes of the values since the last snapshot, by name.
+    fn snapshot(recorder: &DebuggingRecorder) -> HashMap<String, DebugValue> {
+        recorder
+            .snapshotter()
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, .., value)| (key.key().name().to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let path = PathAndQuery::from_static("/etcdserverpb.Watch/Watch");
+
+        let mut resp = ::metrics::with_local_recorder(&recorder, || {
+            let mut request = Request::start(&path);
+            request.finish(Some(&Error::from_kind(ErrKind::NoLeader)));
+
+            let mut resp = tonic::Response::new(());
+            StreamGuard::attach(&mut resp, ActiveStream::new(request.method.clone()));
+            resp
+        });
+
+        let values = snapshot(&recorder);
+        assert_eq!(values["etcd_client_requests_total"], DebugValue::Counter(1));
+        assert_eq!(values["etcd_client_errors_total"], DebugValue::Counter(1));
+        assert_eq!(
+            values["etcd_client_active_streams"],
+            DebugValue::Gauge(1.0.into())
+        );
+
+        // the holder of the stream keeps the guard, and counts the events it receives.
+        let guard = StreamGuard::take(&mut resp);
+        drop(resp);
+        guard.watch_events(2);
+        drop(guard);
+
+        let values = snapshot(&recorder);
+        assert_eq!(
+            values["etcd_client_watch_events_total"],
+            DebugValue::Counter(2)
+        );
+        assert_eq!(
+            values["etcd_client_active_streams"],
+            DebugValue::Gauge((-1.0).into())
+        );
+    }
+
+    #[test]
+    fn test_metrics_cancelled() {
+        let recorder = DebuggingRecorder::new();
+        let path = PathAndQuery::from_static("/etcdserverpb.KV/Range");
+
+        ::metrics::with_local_recorder(&recorder, || drop(Request::start(&path)));
+
+        let errors: Vec<_> = recorder
+            .snapshotter()
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .filter(|(key, ..)| key.key().name() == "etcd_client_errors_total")
+            .collect();
+        assert_eq!(errors.len(), 1);
+        let (key, .., value) = &errors[0];
+        assert!(
+            key.key()
+                .labels()
+                .any(|label| label.key() == "kind" && label.value() == "Cancelled")
+        );
+        assert_eq!(*value, DebugValue::Counter(1));
+    }
+}
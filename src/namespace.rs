@@ -0,0 +1,447 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use futures::StreamExt;
+use http::uri::PathAndQuery;
+use http_body::Frame;
+use http_body_util::StreamBody;
+use prost::bytes::Bytes;
+use tonic::codec::{Codec, ProstCodec, Streaming};
+
+use crate::error::Result;
+use crate::grpc::GrpcService;
+use crate::pb;
+use crate::utils::build_prefix_end;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A service confining all keys under a prefix, like the `namespace` package of the Go client.
+///
+/// The prefix is prepended to the keys and range ends of requests, and removed from the keys
+/// of responses and watch events, so the clients over it see the prefix as the root of the
+/// keyspace. A range end of `\0` means the end of the namespace.
+///
+/// ```no_run
+/// # use etcdv3client::{Client, EtcdClient, Error, Namespaced};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let mut tenant = Client::with_service(Namespaced::new(client.service(), "/tenant-x/"));
+///
+/// // put `/tenant-x/hello`
+/// tenant.put("hello", "world").await?;
+/// // get all keys under `/tenant-x/`, with the prefix removed
+/// let kvs = tenant.all().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Namespaced<C> {
+    namespace: Namespace,
+    inner: C,
+}
+
+impl<C> Namespaced<C>
+where
+    C: GrpcService,
+{
+    pub fn new(inner: C, prefix: impl Into<Vec<u8>>) -> Self {
+        Namespaced {
+            namespace: Namespace {
+                prefix: prefix.into().into(),
+            },
+            inner,
+        }
+    }
+
+    /// The prefix of the namespace.
+    pub fn prefix(&self) -> &[u8] {
+        &self.namespace.prefix
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C> GrpcService for Namespaced<C>
+where
+    C: GrpcService,
+{
+    async fn unary<M, T>(
+        &mut self,
+        mut req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.namespace.prefix_request(req.get_mut());
+
+        let mut resp = self.inner.unary(req, path).await?;
+        self.namespace.strip_response(resp.get_mut());
+
+        Ok(resp)
+    }
+
+    async fn client_streaming<S, M, T>(
+        &mut self,
+        req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let namespace = self.namespace.clone();
+        let req = req.map(|s| {
+            s.map(move |mut msg| {
+                namespace.prefix_request(&mut msg);
+                msg
+            })
+        });
+
+        let mut resp = self.inner.client_streaming(req, path).await?;
+        self.namespace.strip_response(resp.get_mut());
+
+        Ok(resp)
+    }
+
+    async fn server_streaming<M, T>(
+        &mut self,
+        mut req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<Streaming<T>>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.namespace.prefix_request(req.get_mut());
+
+        let resp = self.inner.server_streaming(req, path).await?;
+
+        Ok(resp.map(|s| self.namespace.strip_streaming(s)))
+    }
+
+    async fn streaming<S, M, T>(
+        &mut self,
+        req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<Streaming<T>>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let namespace = self.namespace.clone();
+        let req = req.map(|s| {
+            s.map(move |mut msg| {
+                namespace.prefix_request(&mut msg);
+                msg
+            })
+        });
+
+        let resp = self.inner.streaming(req, path).await?;
+
+        Ok(resp.map(|s| self.namespace.strip_streaming(s)))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Namespace {
+    prefix: Arc<[u8]>,
+}
+
+impl Namespace {
+    fn prefix_key(&self, key: &mut Vec<u8>) {
+        key.splice(0..0, self.prefix.iter().copied());
+    }
+
+    /// Prefix the key range `[key, end)`, an end of `\0` means the end of the namespace.
+    fn prefix_interval(&self, key: &mut Vec<u8>, end: &mut Vec<u8>) {
+        self.prefix_key(key);
+
+        if end.as_slice() == [0] {
+            *end = build_prefix_end(&self.prefix);
+            // the prefix has no end, so it is the end of the keyspace.
+            if end.is_empty() {
+                end.push(0);
+            }
+        } else if !end.is_empty() {
+            self.prefix_key(end);
+        }
+    }
+
+    fn strip_key(&self, key: &mut Vec<u8>) {
+        if key.starts_with(&self.prefix) {
+            key.drain(..self.prefix.len());
+        }
+    }
+
+    fn strip_kv(&self, kv: &mut pb::KeyValue) {
+        self.strip_key(&mut kv.key);
+    }
+
+    fn prefix_leader(&self, leader: &mut Option<pb::LeaderKey>) {
+        if let Some(leader) = leader {
+            self.prefix_key(&mut leader.name);
+            self.prefix_key(&mut leader.key);
+        }
+    }
+
+    fn strip_leader(&self, leader: &mut Option<pb::LeaderKey>) {
+        if let Some(leader) = leader {
+            self.strip_key(&mut leader.name);
+            self.strip_key(&mut leader.key);
+        }
+    }
+
+    fn prefix_request_op(&self, op: &mut pb::RequestOp) {
+        use pb::request_op::Request;
+
+        match op.request.as_mut() {
+            Some(Request::RequestRange(req)) => self.prefix_request(req),
+            Some(Request::RequestPut(req)) => self.prefix_request(req),
+            Some(Request::RequestDeleteRange(req)) => self.prefix_request(req),
+            Some(Request::RequestTxn(req)) => self.prefix_request(req),
+            None => {}
+        }
+    }
+
+    fn strip_response_op(&self, op: &mut pb::ResponseOp) {
+        use pb::response_op::Response;
+
+        match op.response.as_mut() {
+            Some(Response::ResponseRange(resp)) => self.strip_response(resp),
+            Some(Response::ResponsePut(resp)) => self.strip_response(resp),
+            Some(Response::ResponseDeleteRange(resp)) => self.strip_response(resp),
+            Some(Response::ResponseTxn(resp)) => self.strip_response(resp),
+            None => {}
+        }
+    }
+
+    /// Prefix the keys of a request, other requests are kept as is.
+    fn prefix_request(&self, msg: &mut dyn Any) {
+        if let Some(req) = msg.downcast_mut::<pb::RangeRequest>() {
+            self.prefix_interval(&mut req.key, &mut req.range_end);
+        } else if let Some(req) = msg.downcast_mut::<pb::PutRequest>() {
+            self.prefix_key(&mut req.key);
+        } else if let Some(req) = msg.downcast_mut::<pb::DeleteRangeRequest>() {
+            self.prefix_interval(&mut req.key, &mut req.range_end);
+        } else if let Some(req) = msg.downcast_mut::<pb::TxnRequest>() {
+            for cmp in req.compare.iter_mut() {
+                self.prefix_interval(&mut cmp.key, &mut cmp.range_end);
+            }
+            for op in req.success.iter_mut().chain(req.failure.iter_mut()) {
+                self.prefix_request_op(op);
+            }
+        } else if let Some(req) = msg.downcast_mut::<pb::WatchRequest>() {
+            if let Some(pb::watch_request::RequestUnion::CreateRequest(req)) =
+                req.request_union.as_mut()
+            {
+                self.prefix_interval(&mut req.key, &mut req.range_end);
+            }
+        } else if let Some(req) = msg.downcast_mut::<pb::LockRequest>() {
+            self.prefix_key(&mut req.name);
+        } else if let Some(req) = msg.downcast_mut::<pb::UnlockRequest>() {
+            self.prefix_key(&mut req.key);
+        } else if let Some(req) = msg.downcast_mut::<pb::CampaignRequest>() {
+            self.prefix_key(&mut req.name);
+        } else if let Some(req) = msg.downcast_mut::<pb::ProclaimRequest>() {
+            self.prefix_leader(&mut req.leader);
+        } else if let Some(req) = msg.downcast_mut::<pb::LeaderRequest>() {
+            self.prefix_key(&mut req.name);
+        } else if let Some(req) = msg.downcast_mut::<pb::ResignRequest>() {
+            self.prefix_leader(&mut req.leader);
+        }
+    }
+
+    /// Strip the prefix from the keys of a response, other responses are kept as is.
+    fn strip_response(&self, msg: &mut dyn Any) {
+        if let Some(resp) = msg.downcast_mut::<pb::RangeResponse>() {
+            resp.kvs.iter_mut().for_each(|kv| self.strip_kv(kv));
+        } else if let Some(resp) = msg.downcast_mut::<pb::PutResponse>() {
+            resp.prev_kv.iter_mut().for_each(|kv| self.strip_kv(kv));
+        } else if let Some(resp) = msg.downcast_mut::<pb::DeleteRangeResponse>() {
+            resp.prev_kvs.iter_mut().for_each(|kv| self.strip_kv(kv));
+        } else if let Some(resp) = msg.downcast_mut::<pb::TxnResponse>() {
+            resp.responses
+                .iter_mut()
+                .for_each(|op| self.strip_response_op(op));
+        } else if let Some(resp) = msg.downcast_mut::<pb::WatchResponse>() {
+            for ev in resp.events.iter_mut() {
+                ev.kv.iter_mut().for_each(|kv| self.strip_kv(kv));
+                ev.prev_kv.iter_mut().for_each(|kv| self.strip_kv(kv));
+            }
+        } else if let Some(resp) = msg.downcast_mut::<pb::LeaseTimeToLiveResponse>() {
+            // keys out of the namespace are hidden.
+            resp.keys.retain(|key| key.starts_with(&self.prefix));
+            resp.keys.iter_mut().for_each(|key| self.strip_key(key));
+        } else if let Some(resp) = msg.downcast_mut::<pb::LockResponse>() {
+            self.strip_key(&mut resp.key);
+        } else if let Some(resp) = msg.downcast_mut::<pb::CampaignResponse>() {
+            self.strip_leader(&mut resp.leader);
+        } else if let Some(resp) = msg.downcast_mut::<pb::LeaderResponse>() {
+            resp.kv.iter_mut().for_each(|kv| self.strip_kv(kv));
+        }
+    }
+
+    /// Strip the prefix from the messages of a response stream.
+    ///
+    /// The messages are decoded already, so they are encoded again into a new stream.
+    fn strip_streaming<T>(&self, stream: Streaming<T>) -> Streaming<T>
+    where
+        T: prost::Message + Default + Send + 'static,
+    {
+        let has_keys = TypeId::of::<T>() == TypeId::of::<pb::WatchResponse>()
+            || TypeId::of::<T>() == TypeId::of::<pb::LeaderResponse>();
+        if !has_keys {
+            return stream;
+        }
+
+        let namespace = self.clone();
+        let frames = stream.map(move |msg| {
+            match msg {
+                Ok(mut msg) => {
+                    namespace.strip_response(&mut msg);
+                    Ok(Frame::data(encode_frame(&msg)))
+                }
+                // the status is taken back from the error by the decoder.
+                Err(status) => Err(Box::new(status) as BoxError),
+            }
+        });
+
+        Streaming::new_response(
+            ProstCodec::<T, T>::default().decoder(),
+            StreamBody::new(frames),
+            http::StatusCode::OK,
+            None,
+            Some(usize::MAX),
+        )
+    }
+}
+
+/// Encode the message into an uncompressed gRPC frame.
+fn encode_frame<T: prost::Message>(msg: &T) -> Bytes {
+    let len = msg.encoded_len();
+    let mut buf = Vec::with_capacity(5 + len);
+    buf.push(0);
+    buf.extend_from_slice(&(len as u32).to_be_bytes());
+    msg.encode(&mut buf).expect("buffer has enough capacity");
+
+    buf.into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Namespace;
+    use crate::pb;
+    use crate::txn::{Cmp, Op, Txn};
+
+    fn namespace(prefix: &[u8]) -> Namespace {
+        Namespace {
+            prefix: prefix.into(),
+        }
+    }
+
+    #[test]
+    fn test_prefix_interval() {
+        let ns = namespace(b"/t/");
+        let interval = |key: &[u8], end: &[u8]| {
+            let (mut key, mut end) = (key.to_vec(), end.to_vec());
+            ns.prefix_interval(&mut key, &mut end);
+            (key, end)
+        };
+
+        assert_eq!(interval(b"a", b""), (b"/t/a".to_vec(), b"".to_vec()));
+        assert_eq!(interval(b"a", b"b"), (b"/t/a".to_vec(), b"/t/b".to_vec()));
+        assert_eq!(interval(b"\0", b"\0"), (b"/t/\0".to_vec(), b"/t0".to_vec()));
+        assert_eq!(interval(b"a", b"\0"), (b"/t/a".to_vec(), b"/t0".to_vec()));
+
+        let ns = namespace(b"\xff\xff");
+        let mut key = b"a".to_vec();
+        let mut end = b"\0".to_vec();
+        ns.prefix_interval(&mut key, &mut end);
+        assert_eq!((key, end), (b"\xff\xffa".to_vec(), b"\0".to_vec()));
+    }
+
+    #[test]
+    fn test_prefix_txn() {
+        let ns = namespace(b"/t/");
+
+        let mut req = pb::TxnRequest::from(
+            Txn::new()
+                .when([Cmp::version("a").eq(1)])
+                .and_then([Op::txn(Txn::new().and_then([Op::put("b", "1")]))])
+                .or_else([Op::get("c").prefix()]),
+        );
+        ns.prefix_request(&mut req);
+
+        let expected = pb::TxnRequest::from(
+            Txn::new()
+                .when([Cmp::version("/t/a").eq(1)])
+                .and_then([Op::txn(Txn::new().and_then([Op::put("/t/b", "1")]))])
+                .or_else([Op::get("/t/c").range_end("/t/d")]),
+        );
+        assert_eq!(req, expected);
+
+        let kv = |key: &str| pb::KeyValue {
+            key: key.into(),
+            ..Default::default()
+        };
+        let mut resp = pb::TxnResponse {
+            responses: vec![pb::ResponseOp {
+                response: Some(pb::response_op::Response::ResponseRange(
+                    pb::RangeResponse {
+                        kvs: vec![kv("/t/c"), kv("/t/cc")],
+                        ..Default::default()
+                    },
+                )),
+            }],
+            ..Default::default()
+        };
+        ns.strip_response(&mut resp);
+        assert_eq!(
+            resp.responses[0].as_range().unwrap().kvs,
+            vec![kv("c"), kv("cc")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strip_streaming() {
+        use futures::stream;
+        use http_body::Frame;
+        use http_body_util::StreamBody;
+        use tonic::codec::{Codec, ProstCodec, Streaming};
+
+        let ns = namespace(b"/t/");
+
+        let resp = pb::WatchResponse {
+            events: vec![pb::Event {
+                kv: Some(pb::KeyValue {
+                    key: b"/t/a".to_vec(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let frames = stream::iter([Ok::<_, super::BoxError>(Frame::data(super::encode_frame(
+            &resp,
+        )))]);
+        let stream = Streaming::new_response(
+            ProstCodec::<pb::WatchResponse, pb::WatchResponse>::default().decoder(),
+            StreamBody::new(frames),
+            http::StatusCode::OK,
+            None,
+            None,
+        );
+
+        let mut stream = ns.strip_streaming(stream);
+        let resp = stream.message().await.unwrap().unwrap();
+        assert_eq!(resp.events[0].kv.as_ref().unwrap().key, b"a");
+        assert!(stream.message().await.unwrap().is_none());
+    }
+}
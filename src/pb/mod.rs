@@ -10,6 +10,16 @@ pub(crate) mod etcdserverpb {
     include!("etcdserverpb.rs");
 }
 
+pub(crate) mod v3lockpb {
+    include!("v3lockpb.rs");
+}
+
+pub(crate) mod v3electionpb {
+    include!("v3electionpb.rs");
+}
+
 pub use authpb::*;
 pub use etcdserverpb::*;
 pub use mvccpb::*;
+pub use v3electionpb::*;
+pub use v3lockpb::*;
@@ -0,0 +1,39 @@
+// This file is @generated by prost-build.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LockRequest {
+    /// name is the identifier for the distributed shared lock to be acquired.
+    #[prost(bytes = "vec", tag = "1")]
+    pub name: ::prost::alloc::vec::Vec<u8>,
+    /// lease is the ID of the lease that will be attached to ownership of the
+    /// lock. If the lease expires or is revoked and currently holds the lock,
+    /// the lock is automatically released. Calls to Lock with the same lease will
+    /// be treated as a single acquisition; locking twice with the same lease is a
+    /// no-op.
+    #[prost(int64, tag = "2")]
+    pub lease: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LockResponse {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<super::etcdserverpb::ResponseHeader>,
+    /// key is a key that will exist on etcd for the duration that the Lock caller
+    /// owns the lock. Users should not modify this key or the lock may exhibit
+    /// undefined behavior.
+    #[prost(bytes = "vec", tag = "2")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnlockRequest {
+    /// key is the lock ownership key granted by Lock.
+    #[prost(bytes = "vec", tag = "1")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct UnlockResponse {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<super::etcdserverpb::ResponseHeader>,
+}
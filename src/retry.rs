@@ -0,0 +1,286 @@
+use std::any::Any;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{Duration, Instant};
+
+use http::uri::PathAndQuery;
+
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::GrpcService;
+use crate::pb;
+use crate::utils::Backoff;
+
+const DEFAULT_MAX_RETRIES: usize = 3;
+const DEFAULT_JITTER: f64 = 0.2;
+
+/// The methods which can be applied more than once safely.
+const IDEMPOTENT_METHODS: &[&str] = &[
+    "/etcdserverpb.KV/Range",
+    "/etcdserverpb.Lease/LeaseTimeToLive",
+    "/etcdserverpb.Lease/LeaseLeases",
+    "/etcdserverpb.Cluster/MemberList",
+    "/etcdserverpb.Maintenance/Status",
+    "/etcdserverpb.Maintenance/Hash",
+    "/etcdserverpb.Maintenance/HashKV",
+    "/etcdserverpb.Maintenance/Snapshot",
+    "/etcdserverpb.Auth/Authenticate",
+    "/etcdserverpb.Auth/AuthStatus",
+    "/etcdserverpb.Auth/UserGet",
+    "/etcdserverpb.Auth/UserList",
+    "/etcdserverpb.Auth/RoleGet",
+    "/etcdserverpb.Auth/RoleList",
+    "/v3electionpb.Election/Leader",
+    "/v3electionpb.Election/Observe",
+];
+
+/// A service retrying requests failed with transient errors, like no leader or unavailable.
+///
+/// Reads are always retried. Writes are only retried if they were not sent, or rejected by the
+/// server before being applied; otherwise the write may or may not have been applied, and an
+/// `ErrKind::UnknownOutcome` error is returned with the original error as its cause.
+///
+/// Streaming requests are not retried, since the request stream can not be replayed.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use etcdv3client::{Client, EtcdClient, Error, Retry};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let service = Retry::new(client.service())
+///     .with_max_retries(5)
+///     .with_backoff(Duration::from_millis(50), Duration::from_secs(2));
+/// let mut client = Client::with_service(service);
+///
+/// let value = client.get("/hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Retry<C> {
+    inner: C,
+    max_retries: usize,
+    backoff: Backoff,
+    jitter: f64,
+}
+
+impl<C> Retry<C>
+where
+    C: GrpcService,
+{
+    pub fn new(inner: C) -> Self {
+        Retry {
+            inner,
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff: Backoff::default(),
+            jitter: DEFAULT_JITTER,
+        }
+    }
+
+    /// Set the max number of retries of a request, 3 by default.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the exponential backoff between retries, from 100ms to 10s by default.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = Backoff::new(initial, max);
+        self
+    }
+
+    /// Reduce each backoff by a random fraction up to `jitter`, 0.2 by default.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Decide whether to retry the failed request, returns the error to return otherwise.
+    fn check(&self, idempotent: bool, retries: usize, err: Error) -> Result<()> {
+        if !err.is_transient() {
+            return Err(err);
+        }
+        if !idempotent && !err.is_not_sent() {
+            return Err(Error::new(ErrKind::UnknownOutcome, err));
+        }
+        if retries >= self.max_retries {
+            return Err(err);
+        }
+
+        tracing::debug!(?err, retries, "retry request");
+        Ok(())
+    }
+}
+
+impl<C> GrpcService for Retry<C>
+where
+    C: GrpcService,
+{
+    async fn unary<M, T>(
+        &mut self,
+        req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let idempotent = is_idempotent(&path, req.get_ref());
+        let (metadata, extensions, message) = req.into_parts();
+        let mut backoff = self.backoff.clone();
+
+        let mut retries = 0;
+        loop {
+            let req =
+                tonic::Request::from_parts(metadata.clone(), extensions.clone(), message.clone());
+            match self.inner.unary(req, path.clone()).await {
+                Ok(resp) => return Ok(resp),
+                Err(err) => self.check(idempotent, retries, err)?,
+            }
+
+            tokio::time::sleep(jitter(backoff.next_delay(), self.jitter)).await;
+            retries += 1;
+        }
+    }
+
+    async fn client_streaming<S, M, T>(
+        &mut self,
+        req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.inner.client_streaming(req, path).await
+    }
+
+    async fn server_streaming<M, T>(
+        &mut self,
+        req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<tonic::Streaming<T>>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let idempotent = is_idempotent(&path, req.get_ref());
+        let (metadata, extensions, message) = req.into_parts();
+        let mut backoff = self.backoff.clone();
+
+        let mut retries = 0;
+        loop {
+            let req =
+                tonic::Request::from_parts(metadata.clone(), extensions.clone(), message.clone());
+            match self.inner.server_streaming(req, path.clone()).await {
+                Ok(resp) => return Ok(resp),
+                Err(err) => self.check(idempotent, retries, err)?,
+            }
+
+            tokio::time::sleep(jitter(backoff.next_delay(), self.jitter)).await;
+            retries += 1;
+        }
+    }
+
+    async fn streaming<S, M, T>(
+        &mut self,
+        req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<tonic::Streaming<T>>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.inner.streaming(req, path).await
+    }
+}
+
+/// Whether the request can be applied more than once safely, a txn of only ranges is.
+fn is_idempotent(path: &PathAndQuery, msg: &dyn Any) -> bool {
+    if IDEMPOTENT_METHODS.contains(&path.path()) {
+        return true;
+    }
+
+    msg.downcast_ref::<pb::TxnRequest>()
+        .is_some_and(is_read_only_txn)
+}
+
+fn is_read_only_txn(txn: &pb::TxnRequest) -> bool {
+    use pb::request_op::Request;
+
+    txn.success
+        .iter()
+        .chain(txn.failure.iter())
+        .all(|op| match op.request.as_ref() {
+            Some(Request::RequestRange(_)) | None => true,
+            Some(Request::RequestTxn(txn)) => is_read_only_txn(txn),
+            Some(Request::RequestPut(_)) | Some(Request::RequestDeleteRange(_)) => false,
+        })
+}
+
+/// Reduce the delay by a random fraction up to `jitter`.
+fn jitter(delay: Duration, jitter: f64) -> Duration {
+    let random = RandomState::new().hash_one(Instant::now());
+    let fraction = random as f64 / u64::MAX as f64;
+
+    delay.mul_f64(1.0 - jitter * fraction)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use http::uri::PathAndQuery;
+
+    use super::{is_idempotent, jitter};
+    use crate::error::{ErrKind, Error, NotReady};
+    use crate::pb;
+    use crate::txn::{Op, Txn};
+
+    #[test]
+    fn test_is_idempotent() {
+        let range = PathAndQuery::from_static("/etcdserverpb.KV/Range");
+        let put = PathAndQuery::from_static("/etcdserverpb.KV/Put");
+        let txn = PathAndQuery::from_static("/etcdserverpb.KV/Txn");
+
+        assert!(is_idempotent(&range, &pb::RangeRequest::new("a")));
+        assert!(!is_idempotent(&put, &pb::PutRequest::new("a", "1")));
+
+        let read = pb::TxnRequest::from(
+            Txn::new()
+                .and_then([Op::get("a"), Op::txn(Txn::new().or_else([Op::get("b")]))])
+                .or_else([Op::get("c")]),
+        );
+        assert!(is_idempotent(&txn, &read));
+
+        let write = pb::TxnRequest::from(
+            Txn::new()
+                .and_then([Op::get("a")])
+                .or_else([Op::txn(Txn::new().and_then([Op::delete("b")]))]),
+        );
+        assert!(!is_idempotent(&txn, &write));
+    }
+
+    #[test]
+    fn test_is_not_sent() {
+        let not_ready = Error::new(ErrKind::Grpc, NotReady("channel closed".into()));
+        assert!(not_ready.is_not_sent());
+        assert!(!Error::new(ErrKind::Grpc, "unknown").is_not_sent());
+    }
+
+    #[test]
+    fn test_jitter() {
+        let delay = Duration::from_millis(100);
+        for _ in 0..100 {
+            let d = jitter(delay, 0.2);
+            assert!(d <= delay && d >= Duration::from_millis(80));
+        }
+        assert_eq!(jitter(delay, 0.0), delay);
+    }
+}
@@ -0,0 +1,356 @@
+use std::any::Any;
+
+use http::uri::PathAndQuery;
+use opentelemetry::propagation::Injector;
+use tonic::Code;
+use tonic::codec::Streaming;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::error::Result;
+use crate::grpc::GrpcService;
+use crate::pb;
+
+const REDACTED_KEY: &str = "[redacted]";
+
+/// A service opening a span for each request, and injecting its context into the metadata with
+/// the global propagator of `opentelemetry`, like `traceparent` of W3C trace context.
+///
+/// The span is named `etcd`, with the gRPC method and status, and the key and revision of the
+/// request. A request dropped before finished, like timed out by `with_timeout`, is recorded as
+/// cancelled.
+///
+/// Each call through the service has its own span, so wrapped by a [`Retry`](crate::Retry), each
+/// attempt is traced separately.
+///
+/// ```no_run
+/// # use etcdv3client::{Client, EtcdClient, Error, Retry, Tracing};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let service = Retry::new(Tracing::new(client.service()).with_redacted_keys(true));
+/// let mut client = Client::with_service(service);
+///
+/// let value = client.get("/hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tracing<C> {
+    inner: C,
+    redact_keys: bool,
+}
+
+impl<C> Tracing<C>
+where
+    C: GrpcService,
+{
+    pub fn new(inner: C) -> Self {
+        Tracing {
+            inner,
+            redact_keys: false,
+        }
+    }
+
+    /// Record `[redacted]` instead of the keys of requests in the spans.
+    pub fn with_redacted_keys(mut self, redact_keys: bool) -> Self {
+        self.redact_keys = redact_keys;
+        self
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C> GrpcService for Tracing<C>
+where
+    C: GrpcService,
+{
+    async fn unary<M, T>(
+        &mut self,
+        mut req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let span = RpcSpan::open(&path, req.get_ref(), self.redact_keys);
+        inject_context(&span.span, &mut req);
+
+        let result = self
+            .inner
+            .unary(req, path)
+            .instrument(span.span.clone())
+            .await;
+        span.finish(&result);
+
+        result
+    }
+
+    async fn client_streaming<S, M, T>(
+        &mut self,
+        mut req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<T>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let span = RpcSpan::open(&path, &(), self.redact_keys);
+        inject_context(&span.span, &mut req);
+
+        let result = self
+            .inner
+            .client_streaming(req, path)
+            .instrument(span.span.clone())
+            .await;
+        span.finish(&result);
+
+        result
+    }
+
+    async fn server_streaming<M, T>(
+        &mut self,
+        mut req: tonic::Request<M>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<Streaming<T>>>
+    where
+        M: prost::Message + Clone + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let span = RpcSpan::open(&path, req.get_ref(), self.redact_keys);
+        inject_context(&span.span, &mut req);
+
+        let result = self
+            .inner
+            .server_streaming(req, path)
+            .instrument(span.span.clone())
+            .await;
+        span.finish(&result);
+
+        result
+    }
+
+    async fn streaming<S, M, T>(
+        &mut self,
+        mut req: tonic::Request<S>,
+        path: PathAndQuery,
+    ) -> Result<tonic::Response<Streaming<T>>>
+    where
+        S: futures::Stream<Item = M> + Send + 'static,
+        M: prost::Message + Send + Sync + 'static,
+        T: prost::Message + Default + Send + Sync + 'static,
+    {
+        let span = RpcSpan::open(&path, &(), self.redact_keys);
+        inject_context(&span.span, &mut req);
+
+        let result = self
+            .inner
+            .streaming(req, path)
+            .instrument(span.span.clone())
+            .await;
+        span.finish(&result);
+
+        result
+    }
+}
+
+/// The span of a RPC, the RPC is recorded as cancelled if it is dropped before the result.
+struct RpcSpan {
+    span: Span,
+    finished: bool,
+}
+
+impl RpcSpan {
+    fn open(path: &PathAndQuery, msg: &dyn Any, redact_keys: bool) -> Self {
+        RpcSpan {
+            span: rpc_span(path, msg, redact_keys),
+            finished: false,
+        }
+    }
+
+    fn finish<T: Any>(mut self, result: &Result<tonic::Response<T>>) {
+        self.finished = true;
+        record_result(&self.span, result);
+    }
+}
+
+impl Drop for RpcSpan {
+    fn drop(&mut self) {
+        if !self.finished {
+            record_cancelled(&self.span);
+        }
+    }
+}
+
+/// Open the span of a RPC, with the key and revision of the request.
+fn rpc_span(path: &PathAndQuery, msg: &dyn Any, redact_keys: bool) -> Span {
+    let name = path.path().trim_start_matches('/');
+    let (service, method) = name.split_once('/').unwrap_or(("", name));
+
+    let span = tracing::info_span!(
+        "etcd",
+        otel.name = name,
+        otel.kind = "client",
+        otel.status_code = Empty,
+        rpc.system = "grpc",
+        rpc.service = service,
+        rpc.method = method,
+        rpc.grpc.status_code = Empty,
+        error.type = Empty,
+        etcd.key = Empty,
+        etcd.revision = Empty,
+        etcd.header.revision = Empty,
+    );
+    if span.is_disabled() {
+        return span;
+    }
+
+    if let Some(key) = request_key(msg) {
+        if redact_keys {
+            span.record("etcd.key", REDACTED_KEY);
+        } else {
+            span.record("etcd.key", String::from_utf8_lossy(key).as_ref());
+        }
+    }
+    if let Some(revision) = request_revision(msg) {
+        span.record("etcd.revision", revision);
+    }
+
+    span
+}
+
+/// Record the status of the RPC, and the revision of the response.
+fn record_result<T: Any>(span: &Span, result: &Result<tonic::Response<T>>) {
+    match result {
+        Ok(resp) => {
+            span.record("rpc.grpc.status_code", Code::Ok as i32);
+            if let Some(header) = response_header(resp.get_ref()) {
+                span.record("etcd.header.revision", header.revision);
+            }
+        }
+        Err(err) => {
+            let code = err
+                .status()
+                .map(|status| status.code())
+                .unwrap_or(Code::Unknown);
+            span.record("rpc.grpc.status_code", code as i32);
+            span.record("error.type", format!("{:?}", err.kind()));
+            span.record("otel.status_code", "ERROR");
+        }
+    }
+}
+
+/// Record the RPC as cancelled, when it is dropped before the result.
+fn record_cancelled(span: &Span) {
+    span.record("rpc.grpc.status_code", Code::Cancelled as i32);
+    span.record("error.type", "Cancelled");
+    span.record("otel.status_code", "ERROR");
+}
+
+/// Inject the context of the span into the metadata, with the global propagator.
+fn inject_context<M>(span: &Span, req: &mut tonic::Request<M>) {
+    let context = span.context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut MetadataInjector(req.metadata_mut()))
+    });
+}
+
+struct MetadataInjector<'a>(&'a mut MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        match (
+            MetadataKey::from_bytes(key.as_bytes()),
+            MetadataValue::try_from(value),
+        ) {
+            (Ok(key), Ok(value)) => {
+                self.0.insert(key, value);
+            }
+            _ => tracing::warn!(key, "invalid trace context metadata"),
+        }
+    }
+}
+
+fn request_key(msg: &dyn Any) -> Option<&[u8]> {
+    if let Some(req) = msg.downcast_ref::<pb::RangeRequest>() {
+        Some(&req.key)
+    } else if let Some(req) = msg.downcast_ref::<pb::PutRequest>() {
+        Some(&req.key)
+    } else if let Some(req) = msg.downcast_ref::<pb::DeleteRangeRequest>() {
+        Some(&req.key)
+    } else if let Some(req) = msg.downcast_ref::<pb::LockRequest>() {
+        Some(&req.name)
+    } else if let Some(req) = msg.downcast_ref::<pb::CampaignRequest>() {
+        Some(&req.name)
+    } else {
+        None
+    }
+}
+
+fn request_revision(msg: &dyn Any) -> Option<i64> {
+    if let Some(req) = msg.downcast_ref::<pb::RangeRequest>() {
+        Some(req.revision).filter(|revision| *revision > 0)
+    } else {
+        msg.downcast_ref::<pb::CompactionRequest>()
+            .map(|req| req.revision)
+    }
+}
+
+fn response_header(msg: &dyn Any) -> Option<&pb::ResponseHeader> {
+    if let Some(resp) = msg.downcast_ref::<pb::RangeResponse>() {
+        resp.header.as_ref()
+    } else if let Some(resp) = msg.downcast_ref::<pb::PutResponse>() {
+        resp.header.as_ref()
+    } else if let Some(resp) = msg.downcast_ref::<pb::DeleteRangeResponse>() {
+        resp.header.as_ref()
+    } else if let Some(resp) = msg.downcast_ref::<pb::TxnResponse>() {
+        resp.header.as_ref()
+    } else if let Some(resp) = msg.downcast_ref::<pb::CompactionResponse>() {
+        resp.header.as_ref()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use opentelemetry::propagation::Injector;
+    use tonic::metadata::MetadataMap;
+
+    use super::{MetadataInjector, request_key, request_revision};
+    use crate::pb;
+
+    #[test]
+    fn test_request_fields() {
+        let req = pb::RangeRequest {
+            revision: 3,
+            ..pb::RangeRequest::new("a")
+        };
+        assert_eq!(request_key(&req), Some(&b"a"[..]));
+        assert_eq!(request_revision(&req), Some(3));
+
+        let req = pb::RangeRequest::new("a");
+        assert_eq!(request_revision(&req), None);
+
+        let req = pb::TxnRequest::default();
+        assert_eq!(request_key(&req), None);
+    }
+
+    #[test]
+    fn test_metadata_injector() {
+        let mut metadata = MetadataMap::new();
+        let traceparent = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+        MetadataInjector(&mut metadata).set("traceparent", traceparent.to_string());
+        MetadataInjector(&mut metadata).set("invalid key", "value".to_string());
+
+        assert_eq!(metadata.get("traceparent").unwrap(), traceparent);
+        assert_eq!(metadata.len(), 1);
+    }
+}
@@ -0,0 +1,525 @@
+use std::collections::HashSet;
+
+use crate::error::{ErrKind, Error, Result};
+use crate::pb;
+use crate::utils::build_prefix_end;
+
+use pb::compare::{CompareResult, TargetUnion};
+
+/// A transaction, the ops of `and_then` are applied if all compares succeed, otherwise the ops
+/// of `or_else` are applied.
+///
+/// ```no_run
+/// # use etcdv3client::{EtcdClient, Error, Cmp, Op, Txn};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let txn = Txn::new()
+///     .when([Cmp::mod_revision("/config").eq(42)])
+///     .and_then([Op::put("/config", "new").prev_kv()])
+///     .or_else([Op::get("/config")]);
+///
+/// let result = client.kv.commit(txn).await?;
+/// if !result.succeeded() {
+///     println!("config changed: {:?}", result.range(0));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Txn {
+    compare: Vec<Cmp>,
+    success: Vec<Op>,
+    failure: Vec<Op>,
+}
+
+impl Txn {
+    pub fn new() -> Self {
+        Txn::default()
+    }
+
+    /// Add compares, all of them must succeed.
+    pub fn when(mut self, cmps: impl IntoIterator<Item = Cmp>) -> Self {
+        self.compare.extend(cmps);
+        self
+    }
+
+    /// Add ops applied if all compares succeed.
+    pub fn and_then(mut self, ops: impl IntoIterator<Item = Op>) -> Self {
+        self.success.extend(ops);
+        self
+    }
+
+    /// Add ops applied if any compare fails.
+    pub fn or_else(mut self, ops: impl IntoIterator<Item = Op>) -> Self {
+        self.failure.extend(ops);
+        self
+    }
+
+    /// Check the txn on client side, a key must not be modified more than once in a branch.
+    ///
+    /// Returns `ErrKind::DuplicateKey` error like the server does.
+    pub fn check(&self) -> Result<()> {
+        let request = pb::TxnRequest::from(self.clone());
+        check_duplicate_keys(&request)
+    }
+}
+
+impl From<Txn> for pb::TxnRequest {
+    fn from(txn: Txn) -> Self {
+        pb::TxnRequest {
+            compare: txn.compare.into_iter().map(Into::into).collect(),
+            success: txn.success.into_iter().map(Into::into).collect(),
+            failure: txn.failure.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A compare of a txn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cmp(pb::Compare);
+
+impl Cmp {
+    /// Compare the version of the key.
+    pub fn version(key: impl Into<Vec<u8>>) -> CmpTarget<i64> {
+        CmpTarget::new(key, TargetUnion::Version)
+    }
+
+    /// Compare the create revision of the key, `0` means the key does not exist.
+    pub fn create_revision(key: impl Into<Vec<u8>>) -> CmpTarget<i64> {
+        CmpTarget::new(key, TargetUnion::CreateRevision)
+    }
+
+    /// Compare the mod revision of the key.
+    pub fn mod_revision(key: impl Into<Vec<u8>>) -> CmpTarget<i64> {
+        CmpTarget::new(key, TargetUnion::ModRevision)
+    }
+
+    /// Compare the value of the key.
+    pub fn value(key: impl Into<Vec<u8>>) -> CmpTarget<Vec<u8>> {
+        CmpTarget::new(key, TargetUnion::Value)
+    }
+
+    /// Compare the lease of the key.
+    pub fn lease(key: impl Into<Vec<u8>>) -> CmpTarget<i64> {
+        CmpTarget::new(key, TargetUnion::Lease)
+    }
+
+    /// Compare all keys in `[key, end)`.
+    pub fn with_range_end(self, end: impl Into<Vec<u8>>) -> Self {
+        Cmp(self.0.with_range_end(end))
+    }
+
+    /// Compare all keys with the key as prefix.
+    pub fn with_prefix(self) -> Self {
+        Cmp(self.0.with_prefix())
+    }
+}
+
+impl From<Cmp> for pb::Compare {
+    fn from(cmp: Cmp) -> Self {
+        cmp.0
+    }
+}
+
+impl From<pb::Compare> for Cmp {
+    fn from(cmp: pb::Compare) -> Self {
+        Cmp(cmp)
+    }
+}
+
+/// The target of a compare, build a [`Cmp`] with the compare result.
+#[derive(Debug, Clone)]
+pub struct CmpTarget<T> {
+    key: Vec<u8>,
+    target: fn(T) -> TargetUnion,
+}
+
+impl<T> CmpTarget<T> {
+    fn new(key: impl Into<Vec<u8>>, target: fn(T) -> TargetUnion) -> Self {
+        CmpTarget {
+            key: key.into(),
+            target,
+        }
+    }
+
+    fn cmp(self, result: CompareResult, value: T) -> Cmp {
+        Cmp(pb::Compare::new(self.key, result, (self.target)(value)))
+    }
+
+    /// The target equals to `value`.
+    pub fn eq(self, value: impl Into<T>) -> Cmp {
+        self.cmp(CompareResult::Equal, value.into())
+    }
+
+    /// The target does not equal to `value`.
+    pub fn ne(self, value: impl Into<T>) -> Cmp {
+        self.cmp(CompareResult::NotEqual, value.into())
+    }
+
+    /// The target is greater than `value`.
+    pub fn gt(self, value: impl Into<T>) -> Cmp {
+        self.cmp(CompareResult::Greater, value.into())
+    }
+
+    /// The target is less than `value`.
+    pub fn lt(self, value: impl Into<T>) -> Cmp {
+        self.cmp(CompareResult::Less, value.into())
+    }
+}
+
+/// An op of a txn.
+///
+/// The modifiers only apply to the ops they make sense for, like `lease` for put.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    Range(pb::RangeRequest),
+    Put(pb::PutRequest),
+    DeleteRange(pb::DeleteRangeRequest),
+    Txn(pb::TxnRequest),
+}
+
+impl Op {
+    pub fn get(key: impl Into<Vec<u8>>) -> Self {
+        Op::Range(pb::RangeRequest::new(key))
+    }
+
+    pub fn put(key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) -> Self {
+        Op::Put(pb::PutRequest::new(key, value))
+    }
+
+    pub fn delete(key: impl Into<Vec<u8>>) -> Self {
+        Op::DeleteRange(pb::DeleteRangeRequest::new(key))
+    }
+
+    pub fn txn(txn: Txn) -> Self {
+        Op::Txn(txn.into())
+    }
+
+    /// Get or delete all keys in `[key, end)`.
+    pub fn range_end(mut self, end: impl Into<Vec<u8>>) -> Self {
+        match &mut self {
+            Op::Range(req) => req.range_end = end.into(),
+            Op::DeleteRange(req) => req.range_end = end.into(),
+            _ => {}
+        }
+        self
+    }
+
+    /// Get or delete all keys with the key as prefix.
+    pub fn prefix(mut self) -> Self {
+        match &mut self {
+            Op::Range(req) => req.range_end = build_prefix_end(&req.key),
+            Op::DeleteRange(req) => req.range_end = build_prefix_end(&req.key),
+            _ => {}
+        }
+        self
+    }
+
+    /// Attach the put key to the lease.
+    pub fn lease(mut self, lease: i64) -> Self {
+        if let Op::Put(req) = &mut self {
+            req.lease = lease;
+        }
+        self
+    }
+
+    /// Return the previous key-value pair of put or delete.
+    pub fn prev_kv(mut self) -> Self {
+        match &mut self {
+            Op::Put(req) => req.prev_kv = true,
+            Op::DeleteRange(req) => req.prev_kv = true,
+            _ => {}
+        }
+        self
+    }
+
+    /// Limit the number of keys of get.
+    pub fn limit(mut self, limit: i64) -> Self {
+        if let Op::Range(req) = &mut self {
+            req.limit = limit;
+        }
+        self
+    }
+
+    /// Get at the revision.
+    pub fn revision(mut self, revision: i64) -> Self {
+        if let Op::Range(req) = &mut self {
+            req.revision = revision;
+        }
+        self
+    }
+
+    /// Only get the keys, without the values.
+    pub fn keys_only(mut self) -> Self {
+        if let Op::Range(req) = &mut self {
+            req.keys_only = true;
+        }
+        self
+    }
+
+    /// Only get the count of the keys.
+    pub fn count_only(mut self) -> Self {
+        if let Op::Range(req) = &mut self {
+            req.count_only = true;
+        }
+        self
+    }
+}
+
+impl From<Op> for pb::RequestOp {
+    fn from(op: Op) -> Self {
+        match op {
+            Op::Range(req) => req.into(),
+            Op::Put(req) => req.into(),
+            Op::DeleteRange(req) => req.into(),
+            Op::Txn(req) => req.into(),
+        }
+    }
+}
+
+/// The result of a txn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxnResult(pb::TxnResponse);
+
+impl TxnResult {
+    /// Whether all compares succeeded and the `and_then` ops were applied.
+    pub fn succeeded(&self) -> bool {
+        self.0.succeeded
+    }
+
+    pub fn header(&self) -> Option<&pb::ResponseHeader> {
+        self.0.header.as_ref()
+    }
+
+    /// The responses of the applied ops, in the order of the ops.
+    pub fn responses(&self) -> &[pb::ResponseOp] {
+        &self.0.responses
+    }
+
+    /// The response of the op at `index`, if it is a get.
+    pub fn range(&self, index: usize) -> Option<&pb::RangeResponse> {
+        self.0.responses.get(index)?.as_range()
+    }
+
+    /// The response of the op at `index`, if it is a put.
+    pub fn put(&self, index: usize) -> Option<&pb::PutResponse> {
+        self.0.responses.get(index)?.as_put()
+    }
+
+    /// The response of the op at `index`, if it is a delete.
+    pub fn delete_range(&self, index: usize) -> Option<&pb::DeleteRangeResponse> {
+        self.0.responses.get(index)?.as_delete_range()
+    }
+
+    /// The response of the op at `index`, if it is a nested txn.
+    pub fn txn(&self, index: usize) -> Option<TxnResult> {
+        self.0
+            .responses
+            .get(index)?
+            .as_txn()
+            .cloned()
+            .map(TxnResult)
+    }
+
+    pub fn into_inner(self) -> pb::TxnResponse {
+        self.0
+    }
+}
+
+impl From<pb::TxnResponse> for TxnResult {
+    fn from(resp: pb::TxnResponse) -> Self {
+        TxnResult(resp)
+    }
+}
+
+impl pb::ResponseOp {
+    pub fn as_range(&self) -> Option<&pb::RangeResponse> {
+        match self.response.as_ref()? {
+            pb::response_op::Response::ResponseRange(resp) => Some(resp),
+            _ => None,
+        }
+    }
+
+    pub fn as_put(&self) -> Option<&pb::PutResponse> {
+        match self.response.as_ref()? {
+            pb::response_op::Response::ResponsePut(resp) => Some(resp),
+            _ => None,
+        }
+    }
+
+    pub fn as_delete_range(&self) -> Option<&pb::DeleteRangeResponse> {
+        match self.response.as_ref()? {
+            pb::response_op::Response::ResponseDeleteRange(resp) => Some(resp),
+            _ => None,
+        }
+    }
+
+    pub fn as_txn(&self) -> Option<&pb::TxnResponse> {
+        match self.response.as_ref()? {
+            pb::response_op::Response::ResponseTxn(resp) => Some(resp),
+            _ => None,
+        }
+    }
+}
+
+/// Check that no key is modified more than once in each branch of the txn, the same as
+/// the server does.
+pub(crate) fn check_duplicate_keys(request: &pb::TxnRequest) -> Result<()> {
+    check_intervals(&request.success)?;
+    check_intervals(&request.failure)?;
+    Ok(())
+}
+
+/// A deleted key range `[key, end)`, an empty end means a single key, `\0` means no end.
+type Interval = (Vec<u8>, Vec<u8>);
+
+fn intersects(dels: &[Interval], key: &[u8]) -> bool {
+    dels.iter().any(|(begin, end)| match end.as_slice() {
+        [] => begin == key,
+        [0] => begin.as_slice() <= key,
+        end => begin.as_slice() <= key && key < end,
+    })
+}
+
+fn duplicate_key(key: &[u8]) -> Error {
+    Error::new(
+        ErrKind::DuplicateKey,
+        format!(
+            "duplicate key `{}` given in txn request",
+            String::from_utf8_lossy(key)
+        ),
+    )
+}
+
+/// Collect the puts and deletes of the ops, returns error if they overlap.
+fn check_intervals(ops: &[pb::RequestOp]) -> Result<(HashSet<Vec<u8>>, Vec<Interval>)> {
+    use pb::request_op::Request;
+
+    // collect deletes of this level first, to check the puts of lower levels.
+    let mut dels: Vec<Interval> = ops
+        .iter()
+        .filter_map(|op| match op.request.as_ref()? {
+            Request::RequestDeleteRange(req) => Some((req.key.clone(), req.range_end.clone())),
+            _ => None,
+        })
+        .collect();
+
+    let mut puts = HashSet::new();
+
+    for op in ops {
+        let Some(Request::RequestTxn(txn)) = op.request.as_ref() else {
+            continue;
+        };
+
+        let (puts_then, dels_then) = check_intervals(&txn.success)?;
+        let (puts_else, dels_else) = check_intervals(&txn.failure)?;
+
+        for key in puts_then.iter() {
+            if puts.contains(key) || intersects(&dels, key) {
+                return Err(duplicate_key(key));
+            }
+            puts.insert(key.clone());
+        }
+        for key in puts_else {
+            // then and else are exclusive, so the same key in both is fine.
+            if (puts.contains(&key) && !puts_then.contains(&key)) || intersects(&dels, &key) {
+                return Err(duplicate_key(&key));
+            }
+            puts.insert(key);
+        }
+
+        dels.extend(dels_then);
+        dels.extend(dels_else);
+    }
+
+    for op in ops {
+        let Some(Request::RequestPut(req)) = op.request.as_ref() else {
+            continue;
+        };
+
+        if puts.contains(&req.key) || intersects(&dels, &req.key) {
+            return Err(duplicate_key(&req.key));
+        }
+        puts.insert(req.key.clone());
+    }
+
+    Ok((puts, dels))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cmp, Op, Txn};
+    use crate::ErrKind;
+    use crate::pb;
+
+    #[test]
+    fn test_build_txn() {
+        let txn = Txn::new()
+            .when([Cmp::mod_revision("a").eq(1), Cmp::value("b").ne("x")])
+            .and_then([Op::put("a", "1").lease(7)])
+            .or_else([Op::get("a").prefix()]);
+        let request = pb::TxnRequest::from(txn);
+
+        assert_eq!(request.compare.len(), 2);
+        assert_eq!(
+            request.compare[0].target_union,
+            Some(pb::compare::TargetUnion::ModRevision(1))
+        );
+        assert_eq!(
+            request.compare[1].result,
+            pb::compare::CompareResult::NotEqual as i32
+        );
+        assert_eq!(
+            request.success[0].request,
+            Some(pb::request_op::Request::RequestPut(pb::PutRequest {
+                lease: 7,
+                ..pb::PutRequest::new("a", "1")
+            }))
+        );
+        assert_eq!(
+            request.failure[0].request,
+            Some(pb::request_op::Request::RequestRange(
+                pb::RangeRequest::new("a").with_prefix()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_check_duplicate_keys() {
+        let is_duplicate = |txn: Txn| {
+            txn.check()
+                .is_err_and(|err| err.kind() == ErrKind::DuplicateKey)
+        };
+
+        assert!(!is_duplicate(
+            Txn::new()
+                .and_then([Op::put("a", "1"), Op::put("b", "2")])
+                .or_else([Op::put("a", "3")])
+        ));
+        assert!(is_duplicate(
+            Txn::new().and_then([Op::put("a", "1"), Op::put("a", "2")])
+        ));
+        assert!(is_duplicate(
+            Txn::new().and_then([Op::delete("a").prefix(), Op::put("ab", "2")])
+        ));
+        assert!(!is_duplicate(
+            Txn::new().and_then([Op::delete("a"), Op::put("ab", "2")])
+        ));
+        assert!(is_duplicate(Txn::new().and_then([
+            Op::put("a", "1"),
+            Op::txn(Txn::new().and_then([Op::put("a", "2")])),
+        ])));
+        assert!(!is_duplicate(
+            Txn::new().and_then([Op::txn(
+                Txn::new()
+                    .and_then([Op::put("a", "1")])
+                    .or_else([Op::put("a", "2")])
+            )])
+        ));
+        assert!(is_duplicate(Txn::new().and_then([
+            Op::delete("a").range_end("\0"),
+            Op::txn(Txn::new().or_else([Op::put("z", "2")])),
+        ])));
+    }
+}
@@ -1,4 +1,10 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::error::{ErrKind, Error, Result};
+
 pub(crate) const TOKEN_FIELD_NAME: &str = "token";
+pub(crate) const GRPC_TIMEOUT_FIELD_NAME: &str = "grpc-timeout";
 
 pub fn build_prefix_end(prefix: impl AsRef<[u8]>) -> Vec<u8> {
     const NO_PREFIX_END: Vec<u8> = Vec::new();
@@ -16,8 +22,71 @@ pub fn build_prefix_end(prefix: impl AsRef<[u8]>) -> Vec<u8> {
     NO_PREFIX_END
 }
 
+/// Create the request, with the timeout sent to the server as `grpc-timeout`.
+pub(crate) fn new_request<M>(message: M, timeout: Option<Duration>) -> tonic::Request<M> {
+    let mut request = tonic::Request::new(message);
+    if let Some(timeout) = timeout {
+        request.set_timeout(timeout);
+    }
+    request
+}
+
+/// Enforce the timeout locally, in case the server or the connection hangs.
+pub(crate) async fn with_deadline<T>(
+    timeout: Option<Duration>,
+    fut: impl Future<Output = Result<tonic::Response<T>>>,
+) -> Result<T> {
+    let resp = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, fut).await.map_err(|_| {
+            Error::new(
+                ErrKind::DeadlineExceeded,
+                format!("request timed out after {timeout:?}"),
+            )
+        })??,
+        None => fut.await?,
+    };
+
+    Ok(resp.into_inner())
+}
+
+/// Exponential backoff between reconnecting attempts.
+#[derive(Debug, Clone)]
+pub(crate) struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    pub(crate) fn new(initial: Duration, max: Duration) -> Self {
+        Backoff {
+            initial,
+            max,
+            current: initial,
+        }
+    }
+
+    /// Get the delay before the next attempt, and double it for the one after.
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::new(Duration::from_millis(100), Duration::from_secs(10))
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
 
     #[test]
     fn test_build_prefix_end() {
@@ -25,4 +94,38 @@ mod test {
         assert_eq!(super::build_prefix_end(b"abc\xFF"), b"abd".to_vec());
         assert_eq!(super::build_prefix_end(b"\xFF\xFF"), b"".to_vec());
     }
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = super::Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(4));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(5));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(5));
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_with_deadline() {
+        let request = super::new_request((), Some(Duration::from_millis(10)));
+        assert_eq!(request.metadata().get("grpc-timeout").unwrap(), "10000000n");
+
+        let hang = std::future::pending::<crate::error::Result<tonic::Response<()>>>();
+        let err = super::with_deadline(Some(Duration::from_millis(10)), hang)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrKind::DeadlineExceeded);
+
+        let ok = async { Ok(tonic::Response::new(1)) };
+        assert_eq!(super::with_deadline(None, ok).await.unwrap(), 1);
+
+        let status = tonic::Status::cancelled("Timeout expired");
+        assert_eq!(
+            crate::Error::from(status).kind(),
+            crate::ErrKind::DeadlineExceeded
+        );
+    }
 }
@@ -1,16 +1,23 @@
 use std::fmt;
 use std::future::{Future, IntoFuture};
 use std::pin::Pin;
+use std::task::{Context, Poll};
 
-use crate::error::{ErrKind, Error, Result};
-use crate::grpc::GrpcService;
+use futures::{Stream, StreamExt};
+
+use crate::error::{CompactRevision, ErrKind, Error, Result};
+use crate::grpc::{GrpcService, StreamGuard};
 use crate::pb;
-use crate::utils::build_prefix_end;
+use crate::utils::{Backoff, build_prefix_end};
 
 use tokio::sync::mpsc::{Sender, channel};
 use tonic::IntoStreamingRequest;
 use tonic::codec::Streaming;
 
+mod manager;
+
+pub use manager::{ManagedWatcher, WatchManager};
+
 const MPSC_CHANNEL_SIZE: usize = 1;
 
 #[derive(Debug, Clone)]
@@ -73,7 +80,7 @@ where
     /// let resp = WatchClient::new(client.service()).do_watch("hello").with_prefix().await.unwrap();
     /// # Ok(())
     /// # }
-    pub fn do_watch(&mut self, key: impl Into<Vec<u8>>) -> DoCreateWatch<S> {
+    pub fn do_watch(&mut self, key: impl Into<Vec<u8>>) -> DoCreateWatch<'_, S> {
         DoCreateWatch::new(key, self)
     }
 
@@ -119,18 +126,46 @@ where
             .map_err(|err| Error::new(ErrKind::WatchRequestFailed, err))?;
 
         let rx = tokio_stream::wrappers::ReceiverStream::new(req_rx);
-        let mut resp = client.watch(rx.into_streaming_request()).await?;
+        let mut resp = client.inner.watch(rx.into_streaming_request()).await?;
+        let guard = StreamGuard::take(&mut resp);
+        let mut inbound = resp.into_inner();
 
-        let watch_id = match resp.message().await? {
-            Some(msg) => msg.watch_id,
+        let created = match inbound.message().await? {
+            Some(msg) => msg,
             None => return Err(Error::from_kind(ErrKind::WatchStartFailed)),
         };
 
-        let watcher = Watcher::new(watch_id, req_tx, resp);
+        let mut watcher = Watcher::new(created.watch_id, req_tx, inbound);
+        watcher.revision = created.header.map(|h| h.revision).unwrap_or_default();
+        watcher.guard = guard;
 
         Ok(watcher)
     }
 
+    /// Create a watcher which resumes from the last seen revision when the stream is broken.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let mut watcher = client.watch.do_watch("/config").with_prefix().resumable().await?;
+    ///
+    /// while let Some(resp) = watcher.message().await? {
+    ///     println!("got events: {:?}", resp.events);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resumable(self) -> Result<ResumableWatcher<S>> {
+        let client = self.client.clone();
+        let request = self.request.clone();
+
+        let watcher = self.send().await?;
+
+        Ok(ResumableWatcher::new(client, request, watcher))
+    }
+
     /// The key range end to fetch.
     pub fn with_range_end(mut self, end: impl Into<Vec<u8>>) -> Self {
         self.request.range_end = end.into();
@@ -148,6 +183,55 @@ where
         self.request.prev_kv = true;
         self
     }
+
+    /// The revision to watch from (inclusive), watch from the current revision if not set.
+    pub fn with_start_revision(mut self, revision: i64) -> Self {
+        self.request.start_revision = revision;
+        self
+    }
+
+    /// When set, the server periodically sends an empty response if there are no recent events.
+    pub fn with_progress_notify(mut self) -> Self {
+        self.request.progress_notify = true;
+        self
+    }
+
+    /// Filter out events of the given types on server side.
+    pub fn with_filters(mut self, filters: Vec<pb::watch_create_request::FilterType>) -> Self {
+        self.request.filters = filters.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Filter out put events.
+    pub fn with_no_put(mut self) -> Self {
+        let filter = pb::watch_create_request::FilterType::Noput.into();
+        if !self.request.filters.contains(&filter) {
+            self.request.filters.push(filter);
+        }
+        self
+    }
+
+    /// Filter out delete events.
+    pub fn with_no_delete(mut self) -> Self {
+        let filter = pb::watch_create_request::FilterType::Nodelete.into();
+        if !self.request.filters.contains(&filter) {
+            self.request.filters.push(filter);
+        }
+        self
+    }
+
+    /// Allow the server to split large responses into fragments, they are reassembled by the
+    /// watcher.
+    pub fn with_fragment(mut self) -> Self {
+        self.request.fragment = true;
+        self
+    }
+
+    /// Use the given watch id instead of the one assigned by the server.
+    pub fn with_watch_id(mut self, watch_id: i64) -> Self {
+        self.request.watch_id = watch_id;
+        self
+    }
 }
 
 impl<'a, S> fmt::Debug for DoCreateWatch<'a, S> {
@@ -240,8 +324,11 @@ impl From<pb::WatchCancelRequest> for pb::WatchRequest {
 
 pub struct Watcher {
     watch_id: i64,
+    revision: i64,
+    fragments: Option<pb::WatchResponse>,
     req_tx: Sender<pb::WatchRequest>,
     inbound: Streaming<crate::pb::WatchResponse>,
+    guard: StreamGuard,
 }
 
 impl Watcher {
@@ -252,8 +339,11 @@ impl Watcher {
     ) -> Watcher {
         Watcher {
             watch_id,
+            revision: 0,
+            fragments: None,
             req_tx,
             inbound,
+            guard: StreamGuard::default(),
         }
     }
 
@@ -280,13 +370,134 @@ impl Watcher {
     }
 
     pub async fn message(&mut self) -> Result<Option<pb::WatchResponse>> {
-        match self.inbound.message().await? {
-            Some(resp) => Ok(Some(resp)),
-            None => Ok(None),
+        while let Some(resp) = self.inbound.message().await? {
+            self.guard.watch_events(resp.events.len());
+            if let Some(resp) = reassemble(&mut self.fragments, resp) {
+                return Ok(Some(resp));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Watcher {
+    /// Flatten the responses into events along with their revision, until the watch is canceled.
+    ///
+    /// If the watch is canceled by the server, like the start revision is compacted, the stream
+    /// returns an `ErrKind::Compacted` or `ErrKind::WatchCanceled` error before the end.
+    ///
+    /// ```no_run
+    /// # use etcdv3client::{EtcdClient, Error};
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+    /// let mut events = client.watch.watch_prefix("/config").await?.events();
+    ///
+    /// while let Some((revision, event)) = events.next().await.transpose()? {
+    ///     println!("[{}] got event: {:?}", revision, event);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn events(self) -> impl Stream<Item = Result<(i64, pb::Event)>> + Send + Unpin {
+        flatten_events(self)
+    }
+}
+
+impl Stream for Watcher {
+    type Item = Result<pb::WatchResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            match futures::ready!(Pin::new(&mut this.inbound).poll_next(cx)) {
+                Some(Ok(resp)) => {
+                    this.guard.watch_events(resp.events.len());
+                    if let Some(resp) = reassemble(&mut this.fragments, resp) {
+                        return Poll::Ready(Some(Ok(resp)));
+                    }
+                }
+                Some(Err(status)) => return Poll::Ready(Some(Err(status.into()))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Merge a fragment into the pending ones, returns the complete response with the last fragment.
+pub(crate) fn reassemble(
+    fragments: &mut Option<pb::WatchResponse>,
+    resp: pb::WatchResponse,
+) -> Option<pb::WatchResponse> {
+    let resp = match fragments.take() {
+        Some(mut merged) => {
+            merged.events.extend(resp.events);
+            merged.fragment = resp.fragment;
+            merged
         }
+        None => resp,
+    };
+
+    if resp.fragment {
+        fragments.replace(resp);
+        None
+    } else {
+        Some(resp)
+    }
+}
+
+/// The error of a watch canceled by the server, `ErrKind::Compacted` if the revision is compacted.
+pub(crate) fn cancel_error(resp: pb::WatchResponse) -> Error {
+    if resp.compact_revision > 0 {
+        Error::new(ErrKind::Compacted, CompactRevision(resp.compact_revision))
+    } else {
+        Error::new(ErrKind::WatchCanceled, resp.cancel_reason)
     }
 }
 
+/// Flatten watch responses into `(revision, event)`, stop at the canceled response.
+///
+/// If the watch is canceled by the server, the error of the cancel is returned before the end.
+pub(crate) fn flatten_events<W>(watcher: W) -> impl Stream<Item = Result<(i64, pb::Event)>>
+where
+    W: Stream<Item = Result<pb::WatchResponse>>,
+{
+    watcher
+        .scan(false, |canceled, resp| {
+            if *canceled {
+                return std::future::ready(None);
+            }
+            let resp = match resp {
+                Ok(resp) if resp.canceled => {
+                    *canceled = true;
+                    if resp.compact_revision > 0 || !resp.cancel_reason.is_empty() {
+                        Err(cancel_error(resp))
+                    } else {
+                        Ok(resp)
+                    }
+                }
+                resp => resp,
+            };
+            std::future::ready(Some(resp))
+        })
+        .flat_map(|resp| {
+            let events = match resp {
+                Ok(resp) => resp
+                    .events
+                    .into_iter()
+                    .map(|ev| {
+                        let revision = ev.kv.as_ref().map(|kv| kv.mod_revision);
+                        Ok((revision.unwrap_or_default(), ev))
+                    })
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            };
+            futures::stream::iter(events)
+        })
+}
+
 impl fmt::Debug for Watcher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Watcher")
@@ -294,3 +505,225 @@ impl fmt::Debug for Watcher {
             .finish()
     }
 }
+
+/// A watcher which survives broken streams.
+///
+/// The last seen revision is tracked, once the stream is broken or the server is unavailable,
+/// the watch is re-created from the next revision with exponential backoff. Other errors are
+/// returned to the caller.
+///
+/// If the next revision has been compacted, `ErrKind::Compacted` error is returned with the
+/// compact revision, see [`Error::compact_revision`], the caller should resync and watch again.
+pub struct ResumableWatcher<S> {
+    client: WatchClient<S>,
+    request: pb::WatchCreateRequest,
+    watcher: Option<Watcher>,
+    revision: i64,
+    backoff: Backoff,
+    finished: bool,
+}
+
+impl<S> ResumableWatcher<S>
+where
+    S: GrpcService,
+{
+    fn new(client: WatchClient<S>, request: pb::WatchCreateRequest, watcher: Watcher) -> Self {
+        let revision = if request.start_revision > 0 {
+            request.start_revision - 1
+        } else {
+            watcher.revision
+        };
+
+        ResumableWatcher {
+            client,
+            request,
+            watcher: Some(watcher),
+            revision,
+            backoff: Backoff::default(),
+            finished: false,
+        }
+    }
+
+    /// The last seen revision, the watch is resumed from the next one.
+    pub fn revision(&self) -> i64 {
+        self.revision
+    }
+
+    pub async fn progress(&mut self) -> Result<()> {
+        match self.watcher.as_mut() {
+            Some(watcher) => watcher.progress().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Cancel the watch, then `message` returns `None`.
+    pub async fn cancel(&mut self) -> Result<()> {
+        self.finished = true;
+        match self.watcher.take() {
+            Some(mut watcher) => watcher.cancel().await,
+            None => Ok(()),
+        }
+    }
+
+    pub async fn message(&mut self) -> Result<Option<pb::WatchResponse>> {
+        loop {
+            if self.finished {
+                return Ok(None);
+            }
+
+            let Some(watcher) = self.watcher.as_mut() else {
+                self.resume().await?;
+                continue;
+            };
+
+            match watcher.message().await {
+                Ok(Some(resp)) => {
+                    if resp.compact_revision > 0 || resp.canceled {
+                        self.finished = true;
+                        self.watcher = None;
+                        return Err(cancel_error(resp));
+                    }
+
+                    self.backoff.reset();
+                    match resp.events.last().and_then(|ev| ev.kv.as_ref()) {
+                        Some(kv) => self.revision = kv.mod_revision,
+                        None => {
+                            let revision = resp.header.map(|h| h.revision).unwrap_or_default();
+                            self.revision = self.revision.max(revision);
+                        }
+                    }
+
+                    return Ok(Some(resp));
+                }
+                Ok(None) => {
+                    tracing::warn!(revision = self.revision, "watch stream finished");
+                    self.watcher = None;
+                }
+                Err(err) if err.is_unavailable() => {
+                    tracing::warn!(revision = self.revision, ?err, "watch stream broken");
+                    self.watcher = None;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Re-create the watch from the next revision.
+    async fn resume(&mut self) -> Result<()> {
+        loop {
+            tokio::time::sleep(self.backoff.next_delay()).await;
+
+            let mut create_watch = self.client.do_watch(self.request.key.clone());
+            create_watch.request = pb::WatchCreateRequest {
+                start_revision: self.revision + 1,
+                ..self.request.clone()
+            };
+
+            match create_watch.await {
+                Ok(watcher) => {
+                    tracing::debug!(revision = self.revision, "watch resumed");
+                    self.watcher = Some(watcher);
+                    return Ok(());
+                }
+                Err(err) if err.is_unavailable() => {
+                    tracing::warn!(revision = self.revision, ?err, "resume watch failed");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl<S> fmt::Debug for ResumableWatcher<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumableWatcher")
+            .field("request", &self.request)
+            .field("revision", &self.revision)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pb;
+
+    fn response(watch_id: i64, revisions: &[i64], fragment: bool) -> pb::WatchResponse {
+        let events = revisions
+            .iter()
+            .map(|rev| pb::Event {
+                kv: Some(pb::KeyValue {
+                    mod_revision: *rev,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .collect();
+
+        pb::WatchResponse {
+            watch_id,
+            events,
+            fragment,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reassemble() {
+        let mut fragments = None;
+
+        let resp = super::reassemble(&mut fragments, response(1, &[1, 2, 3], false));
+        assert_eq!(resp.map(|r| r.events.len()), Some(3));
+        assert!(fragments.is_none());
+
+        assert!(super::reassemble(&mut fragments, response(1, &[4, 5], true)).is_none());
+        assert!(super::reassemble(&mut fragments, response(1, &[6], true)).is_none());
+
+        let resp = super::reassemble(&mut fragments, response(1, &[7], false)).unwrap();
+        let revisions: Vec<i64> = resp
+            .events
+            .iter()
+            .map(|ev| ev.kv.as_ref().unwrap().mod_revision)
+            .collect();
+        assert_eq!(revisions, vec![4, 5, 6, 7]);
+        assert!(!resp.fragment);
+        assert!(fragments.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_flatten_events() {
+        use futures::StreamExt;
+
+        use crate::error::ErrKind;
+
+        let compacted = pb::WatchResponse {
+            canceled: true,
+            compact_revision: 5,
+            ..response(1, &[], false)
+        };
+        let responses = [
+            Ok(response(1, &[1, 2], false)),
+            Ok(compacted),
+            Ok(response(1, &[6], false)),
+        ];
+        let events: Vec<_> = super::flatten_events(futures::stream::iter(responses))
+            .collect()
+            .await;
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[1].as_ref().unwrap().0, 2);
+        let err = events[2].as_ref().unwrap_err();
+        assert_eq!(err.kind(), ErrKind::Compacted);
+        assert_eq!(err.compact_revision(), Some(5));
+
+        // canceled by the client
+        let canceled = pb::WatchResponse {
+            canceled: true,
+            ..response(1, &[], false)
+        };
+        let responses = [Ok(response(1, &[1], false)), Ok(canceled)];
+        let events: Vec<_> = super::flatten_events(futures::stream::iter(responses))
+            .collect()
+            .await;
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_ok());
+    }
+}
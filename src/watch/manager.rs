@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use tokio::sync::mpsc::{
+    UnboundedReceiver, UnboundedSender, WeakUnboundedSender, unbounded_channel,
+};
+use tokio::sync::oneshot;
+use tonic::IntoStreamingRequest;
+use tonic::codec::Streaming;
+
+use crate::error::{ErrKind, Error, Result};
+use crate::grpc::{GrpcService, StreamGuard};
+use crate::pb;
+
+use super::{WatchClient, flatten_events, reassemble};
+
+/// The watch id of progress notifications for the whole stream.
+const STREAM_WATCH_ID: i64 = -1;
+
+type Routes = Arc<Mutex<HashMap<i64, Route>>>;
+
+struct Route {
+    created: Option<oneshot::Sender<Result<pb::WatchResponse>>>,
+    events: UnboundedSender<Result<pb::WatchResponse>>,
+    fragments: Option<pb::WatchResponse>,
+}
+
+struct ActiveStream {
+    req_tx: UnboundedSender<pb::WatchRequest>,
+    closed: Arc<AtomicBool>,
+}
+
+/// Multiplex many watches over one gRPC `Watch` stream.
+///
+/// The stream is opened by the first watch, and re-opened by the next watch once it is broken.
+/// Watch ids are chosen by the manager, responses are routed to the [`ManagedWatcher`] by the
+/// watch id.
+///
+/// Responses are buffered for each watcher until they are consumed, so that a slow watcher does
+/// not block the others.
+///
+/// ```no_run
+/// # use etcdv3client::{EtcdClient, Error, WatchManager};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = EtcdClient::new(vec!["localhost:2379"], None).await?;
+/// let manager = WatchManager::new(client.watch.clone());
+///
+/// let mut foo = manager.watch_prefix("/foo").await?;
+/// let mut bar = manager.watch_prefix("/bar").await?;
+///
+/// while let Some(resp) = foo.message().await? {
+///     println!("got events: {:?}", resp.events);
+/// }
+/// bar.cancel().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WatchManager<S> {
+    stream: Arc<tokio::sync::Mutex<(WatchClient<S>, Option<ActiveStream>)>>,
+    routes: Routes,
+    next_id: Arc<AtomicI64>,
+}
+
+impl<S> WatchManager<S>
+where
+    S: GrpcService + 'static,
+{
+    pub fn new(client: WatchClient<S>) -> Self {
+        WatchManager {
+            stream: Arc::new(tokio::sync::Mutex::new((client, None))),
+            routes: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicI64::new(1)),
+        }
+    }
+
+    /// Add a watch, the `watch_id` of the request is chosen by the manager.
+    pub async fn watch(&self, mut request: pb::WatchCreateRequest) -> Result<ManagedWatcher> {
+        let watch_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        request.watch_id = watch_id;
+
+        let (created_tx, created_rx) = oneshot::channel();
+        let (events_tx, events) = unbounded_channel();
+        let route = Route {
+            created: Some(created_tx),
+            events: events_tx,
+            fragments: None,
+        };
+        self.routes.lock().unwrap().insert(watch_id, route);
+
+        let created = match self.send(request.into()).await {
+            Ok(req_tx) => created_rx
+                .await
+                .unwrap_or_else(|_| Err(Error::from_kind(ErrKind::WatchStartFailed)))
+                .map(|created| (created, req_tx)),
+            Err(err) => Err(err),
+        };
+
+        let (created, req_tx) = match created {
+            Ok(created) => created,
+            Err(err) => {
+                self.routes.lock().unwrap().remove(&watch_id);
+                return Err(err);
+            }
+        };
+
+        if created.canceled {
+            self.routes.lock().unwrap().remove(&watch_id);
+            return Err(Error::new(ErrKind::WatchCanceled, created.cancel_reason));
+        }
+
+        Ok(ManagedWatcher {
+            watch_id,
+            req_tx,
+            events,
+            routes: self.routes.clone(),
+        })
+    }
+
+    /// Watch a key.
+    pub async fn watch_key(&self, key: impl Into<Vec<u8>>) -> Result<ManagedWatcher> {
+        self.watch(pb::WatchCreateRequest::new(key)).await
+    }
+
+    /// Watch keys with prefix.
+    pub async fn watch_prefix(&self, key: impl Into<Vec<u8>>) -> Result<ManagedWatcher> {
+        let key = key.into();
+        let request = pb::WatchCreateRequest {
+            range_end: crate::utils::build_prefix_end(&key),
+            ..pb::WatchCreateRequest::new(key)
+        };
+
+        self.watch(request).await
+    }
+
+    /// Request a progress notification for all watches of the stream.
+    pub async fn progress(&self) -> Result<()> {
+        self.send(pb::WatchRequest::progress_watch()).await?;
+        Ok(())
+    }
+
+    /// Send the request on the current stream, open a new stream if there is no one.
+    async fn send(&self, request: pb::WatchRequest) -> Result<UnboundedSender<pb::WatchRequest>> {
+        let mut guard = self.stream.lock().await;
+        let (client, stream) = &mut *guard;
+
+        if let Some(stream) = stream
+            .as_ref()
+            .filter(|s| !s.closed.load(Ordering::Acquire))
+        {
+            stream
+                .req_tx
+                .send(request)
+                .map_err(|err| Error::new(ErrKind::WatchRequestFailed, err))?;
+            return Ok(stream.req_tx.clone());
+        }
+
+        let (req_tx, req_rx) = unbounded_channel();
+        req_tx
+            .send(request)
+            .map_err(|err| Error::new(ErrKind::WatchRequestFailed, err))?;
+
+        let rx = tokio_stream::wrappers::UnboundedReceiverStream::new(req_rx);
+        let mut resp = client.inner.watch(rx.into_streaming_request()).await?;
+        let guard = StreamGuard::take(&mut resp);
+
+        let closed = Arc::new(AtomicBool::new(false));
+        tokio::spawn(dispatch(
+            resp.into_inner(),
+            guard,
+            self.routes.clone(),
+            req_tx.downgrade(),
+            closed.clone(),
+        ));
+
+        *stream = Some(ActiveStream {
+            req_tx: req_tx.clone(),
+            closed,
+        });
+
+        Ok(req_tx)
+    }
+}
+
+impl<S> Clone for WatchManager<S> {
+    fn clone(&self) -> Self {
+        WatchManager {
+            stream: self.stream.clone(),
+            routes: self.routes.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for WatchManager<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchManager")
+            .field("watches", &self.routes.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Route the responses of the stream to the watchers, until the stream is broken.
+async fn dispatch(
+    mut inbound: Streaming<pb::WatchResponse>,
+    guard: StreamGuard,
+    routes: Routes,
+    req_tx: WeakUnboundedSender<pb::WatchRequest>,
+    closed: Arc<AtomicBool>,
+) {
+    let status = loop {
+        let resp = match inbound.message().await {
+            Ok(Some(resp)) => resp,
+            Ok(None) => break None,
+            Err(status) => break Some(status),
+        };
+        guard.watch_events(resp.events.len());
+
+        let watch_id = resp.watch_id;
+
+        if watch_id == STREAM_WATCH_ID {
+            let watchers: Vec<_> = routes
+                .lock()
+                .unwrap()
+                .values()
+                .map(|route| route.events.clone())
+                .collect();
+            for events in watchers {
+                let _ = events.send(Ok(resp.clone()));
+            }
+            continue;
+        }
+
+        let (created, events, resp) = {
+            let mut routes = routes.lock().unwrap();
+            let Some(route) = routes.get_mut(&watch_id) else {
+                if !resp.canceled {
+                    cancel_watch(&req_tx, watch_id);
+                }
+                continue;
+            };
+
+            let created = if resp.created {
+                route.created.take()
+            } else {
+                None
+            };
+            let events = route.events.clone();
+            let resp = if resp.created || resp.canceled {
+                Some(resp)
+            } else {
+                reassemble(&mut route.fragments, resp)
+            };
+            if resp.as_ref().is_some_and(|resp| resp.canceled) {
+                routes.remove(&watch_id);
+            }
+
+            (created, events, resp)
+        };
+
+        let Some(resp) = resp else {
+            continue;
+        };
+
+        match created {
+            Some(created) => {
+                let _ = created.send(Ok(resp));
+            }
+            None => {
+                // the watcher is dropped
+                if let Err(_closed) = events.send(Ok(resp)) {
+                    routes.lock().unwrap().remove(&watch_id);
+                    cancel_watch(&req_tx, watch_id);
+                }
+            }
+        }
+    };
+
+    tracing::debug!(?status, "watch stream broken");
+
+    // mark closed before draining, so that new watches either see the flag or get drained.
+    closed.store(true, Ordering::Release);
+
+    let drained: Vec<Route> = routes.lock().unwrap().drain().map(|(_, r)| r).collect();
+    for route in drained {
+        let err = match status {
+            Some(ref status) => Error::from(status.clone()),
+            None => Error::new(ErrKind::WatchFinished, "watch stream finished"),
+        };
+        match route.created {
+            Some(created) => {
+                let _ = created.send(Err(err));
+            }
+            None => {
+                let _ = route.events.send(Err(err));
+            }
+        }
+    }
+}
+
+fn cancel_watch(req_tx: &WeakUnboundedSender<pb::WatchRequest>, watch_id: i64) {
+    if let Some(req_tx) = req_tx.upgrade() {
+        let _ = req_tx.send(pb::WatchRequest::cancel_watch(watch_id));
+    }
+}
+
+/// A watch added by [`WatchManager`].
+///
+/// The watch is canceled when dropped.
+pub struct ManagedWatcher {
+    watch_id: i64,
+    req_tx: UnboundedSender<pb::WatchRequest>,
+    events: UnboundedReceiver<Result<pb::WatchResponse>>,
+    routes: Routes,
+}
+
+impl ManagedWatcher {
+    pub fn watch_id(&self) -> i64 {
+        self.watch_id
+    }
+
+    /// Cancel the watch, `message` returns the canceled response and then `None`.
+    pub async fn cancel(&mut self) -> Result<()> {
+        let request = pb::WatchRequest::cancel_watch(self.watch_id);
+
+        self.req_tx
+            .send(request)
+            .map_err(|err| Error::new(ErrKind::WatchRequestFailed, err))?;
+
+        Ok(())
+    }
+
+    pub async fn message(&mut self) -> Result<Option<pb::WatchResponse>> {
+        match self.events.recv().await {
+            Some(Ok(resp)) => Ok(Some(resp)),
+            Some(Err(err)) => Err(err),
+            None => Ok(None),
+        }
+    }
+}
+
+impl ManagedWatcher {
+    /// Flatten the responses into events along with their revision, until the watch is canceled.
+    pub fn events(self) -> impl Stream<Item = Result<(i64, pb::Event)>> + Send + Unpin {
+        flatten_events(self)
+    }
+}
+
+impl Stream for ManagedWatcher {
+    type Item = Result<pb::WatchResponse>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for ManagedWatcher {
+    fn drop(&mut self) {
+        if self.routes.lock().unwrap().remove(&self.watch_id).is_some() {
+            let _ = self
+                .req_tx
+                .send(pb::WatchRequest::cancel_watch(self.watch_id));
+        }
+    }
+}
+
+impl fmt::Debug for ManagedWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManagedWatcher")
+            .field("watch_id", &self.watch_id)
+            .finish()
+    }
+}